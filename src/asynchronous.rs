@@ -0,0 +1,121 @@
+use std::{
+    fmt::{Debug, Display},
+    future::Future,
+    panic::Location,
+};
+
+use anyhow::Error;
+
+use crate::{
+    get_title, make_fatal_request, make_request, quick_panic_at, report, throttle, DialogRequest,
+    Severity,
+};
+
+/// [`ErrorDialogUnwrapper`](crate::ErrorDialogUnwrapper)の非同期版です。
+/// ダイアログが閉じられるのを待つ間、非同期ランタイムのスレッドをブロックしません。
+pub trait AsyncErrorDialogUnwrapper<T, E = Error>: Sized {
+    fn unwrap_or_dialog_async(self) -> impl Future<Output = T> + Send;
+    fn unwrap_or_dialog_with_title_async(
+        self,
+        title: impl Display,
+    ) -> impl Future<Output = T> + Send;
+
+    fn ok_unwrap_or_dialog_async(self) -> impl Future<Output = Option<T>> + Send;
+    fn ok_unwrap_or_dialog_with_title_async(
+        self,
+        title: impl Display,
+    ) -> impl Future<Output = Option<T>> + Send;
+}
+
+/// [`show_error_dialog`](crate::show_error_dialog)の非同期版です。
+/// ダイアログが閉じられるとタイトルとエラーの全文を返します。
+/// [`Throttle`](crate::Throttle)の設定によっては、ダイアログを表示せずにすぐに返します。
+#[track_caller]
+pub fn show_error_dialog_async<E: Debug>(
+    title: &str,
+    e: E,
+) -> impl Future<Output = (String, String)> + Send + 'static {
+    let mut request = make_request(
+        title,
+        format!("{:?}", e),
+        Severity::Error,
+        true,
+        Some(Location::caller()),
+    );
+    let admitted = throttle::admit(&mut request);
+    show_request_async(request, admitted)
+}
+
+/// ダイアログを表示します。`show`が`false`の場合は、何も表示せずにすぐに返します。
+fn show_request_async(
+    request: DialogRequest,
+    show: bool,
+) -> impl Future<Output = (String, String)> + Send + 'static {
+    let (title, text) = (request.title.clone(), request.text.clone());
+    let dialog = show.then(|| report::show_with_report_async(request));
+
+    async move {
+        if let Some(dialog) = dialog {
+            dialog.await;
+        }
+        (title, text)
+    }
+}
+
+impl<T: Send, E: Debug> AsyncErrorDialogUnwrapper<T, E> for Result<T, E> {
+    #[track_caller]
+    fn unwrap_or_dialog_async(self) -> impl Future<Output = T> + Send {
+        self.unwrap_or_dialog_with_title_async(get_title())
+    }
+
+    #[track_caller]
+    fn unwrap_or_dialog_with_title_async(
+        self,
+        title: impl Display,
+    ) -> impl Future<Output = T> + Send {
+        let location = Location::caller();
+        let result = match self {
+            Ok(v) => Ok(v),
+            Err(e) => Err(show_request_async(
+                make_fatal_request(&format!("{}", title), &e),
+                true,
+            )),
+        };
+
+        async move {
+            match result {
+                Ok(v) => v,
+                Err(dialog) => {
+                    let (title, text) = dialog.await;
+                    quick_panic_at((&title, text), location)
+                }
+            }
+        }
+    }
+
+    #[track_caller]
+    fn ok_unwrap_or_dialog_async(self) -> impl Future<Output = Option<T>> + Send {
+        self.ok_unwrap_or_dialog_with_title_async(get_title())
+    }
+
+    #[track_caller]
+    fn ok_unwrap_or_dialog_with_title_async(
+        self,
+        title: impl Display,
+    ) -> impl Future<Output = Option<T>> + Send {
+        let result = match self {
+            Ok(v) => Ok(v),
+            Err(e) => Err(show_error_dialog_async(&format!("{}", title), e)),
+        };
+
+        async move {
+            match result {
+                Ok(v) => Some(v),
+                Err(dialog) => {
+                    dialog.await;
+                    None
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use std::{
+    any::type_name,
+    cell::RefCell,
+    future::{ready, Future},
+    io,
+    panic::Location,
+    pin::Pin,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+use rfd::{AsyncMessageDialog, MessageButtons, MessageDialog, MessageDialogResult, MessageLevel};
+
+use crate::{
+    dispatcher, get_display_mode, is_gui_available, DisplayMode, ParentWindow, TerminalBackend,
+};
+
+/// ダイアログの重要度です。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Severity {
+    #[default]
+    Error,
+    Warning,
+    Info,
+}
+
+impl From<Severity> for MessageLevel {
+    fn from(severity: Severity) -> Self {
+        match severity {
+            Severity::Error => MessageLevel::Error,
+            Severity::Warning => MessageLevel::Warning,
+            Severity::Info => MessageLevel::Info,
+        }
+    }
+}
+
+/// バックエンドに渡される、表示するダイアログの内容です。
+#[derive(Debug, Clone)]
+pub struct DialogRequest {
+    pub title: String,
+    /// 省略されていない、エラーの説明の全文です。
+    pub text: String,
+    /// ダイアログに表示する、省略済みの説明です。
+    pub description: String,
+    pub severity: Severity,
+    pub buttons: MessageButtons,
+    /// `true`の場合、呼び出し元はダイアログが閉じられるまで待ちます。
+    pub blocking: bool,
+    /// `unwrap_or_dialog`などが呼び出された場所です。パニックフックからの場合は`None`になります。
+    pub location: Option<&'static Location<'static>>,
+    /// ダイアログの親にするウィンドウです。
+    pub parent: Option<ParentWindow>,
+}
+
+/// [`DialogBackend::show_async`]が返すFutureです。
+pub type DialogFuture = Pin<Box<dyn Future<Output = MessageDialogResult> + Send>>;
+
+/// ダイアログを実際に表示するバックエンドです。
+/// [`set_backend`]で差し替えることで、独自のUIやテスト用の記録先にダイアログを流せます。
+pub trait DialogBackend: Send + Sync {
+    /// ダイアログを表示し、押されたボタンを返します。
+    /// [`DialogRequest::blocking`]が`false`の場合は閉じられるのを待たずに返して構いません。
+    fn show(&self, request: &DialogRequest) -> MessageDialogResult;
+
+    /// 非同期ランタイムのスレッドをブロックせずにダイアログを表示します。
+    /// デフォルトでは[`DialogBackend::show`]をそのまま呼び出します。
+    fn show_async(&self, request: DialogRequest) -> DialogFuture {
+        Box::pin(ready(self.show(&request)))
+    }
+
+    /// このバックエンドでダイアログを表示できる環境かどうかを返します。デフォルトでは常に`true`です。
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    /// ダイアログの表示を試み、表示できなかった場合はエラーを返します。
+    /// [`FallbackBackend`](crate::FallbackBackend)は、エラーの場合に次のバックエンドを試します。
+    /// デフォルトでは[`DialogBackend::show`]を呼び出し、常に表示できたものとみなします。
+    fn try_show(&self, request: &DialogRequest) -> io::Result<MessageDialogResult> {
+        Ok(self.show(request))
+    }
+
+    /// ログなどで使われる、バックエンドの名前です。
+    fn name(&self) -> &str {
+        type_name::<Self>()
+    }
+}
+
+/// `rfd`を使ってネイティブのダイアログを表示する、デフォルトのバックエンドです。
+/// ダイアログは専用のスレッドで一つずつ順番に表示され、互いに重なりません。
+/// [`MainThreadDispatcher`](crate::MainThreadDispatcher)のメインスレッドで閉じられるのを待つダイアログは、
+/// デッドロックを避けるためにその場で表示されるため、専用のスレッドのダイアログと同時に表示されることがあります。
+/// [`DialogBackend::show_async`]では`rfd`の非同期のダイアログを使い、専用のスレッドのダイアログと同じ順番待ちで一つずつ表示します。
+///
+/// macOSでは、メインスレッド以外からダイアログを表示するには、`NSApplication`のイベントループが動いているか、
+/// [`MainThreadDispatcher`](crate::MainThreadDispatcher)が設定されている必要があります。
+/// どちらも無い場合、他のスレッドで起きたエラーのダイアログは表示されず、閉じられたものとして扱われます。
+/// フックが設定されていない場合、メインスレッドで起きたエラーは、閉じられるのを待たないものもその場で閉じられるまで表示されます。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RfdBackend;
+
+impl RfdBackend {
+    pub(crate) fn show_blocking(request: &DialogRequest) -> MessageDialogResult {
+        let mut dialog = MessageDialog::new()
+            .set_level(request.severity.into())
+            .set_title(&request.title)
+            .set_description(&request.description)
+            .set_buttons(request.buttons.clone());
+        if let Some(parent) = &request.parent {
+            dialog = dialog.set_parent(parent);
+        }
+        dialog.show()
+    }
+
+    pub(crate) fn show_async_native(
+        request: &DialogRequest,
+    ) -> impl Future<Output = MessageDialogResult> + Send + 'static {
+        let mut dialog = AsyncMessageDialog::new()
+            .set_level(request.severity.into())
+            .set_title(&request.title)
+            .set_description(&request.description)
+            .set_buttons(request.buttons.clone());
+        if let Some(parent) = &request.parent {
+            dialog = dialog.set_parent(parent);
+        }
+        dialog.show()
+    }
+}
+
+impl DialogBackend for RfdBackend {
+    /// メインスレッド以外からのダイアログは、専用のスレッドで一つずつ順番に表示されます。
+    fn show(&self, request: &DialogRequest) -> MessageDialogResult {
+        dispatcher::show(request)
+    }
+
+    fn show_async(&self, request: DialogRequest) -> DialogFuture {
+        dispatcher::show_async(request)
+    }
+
+    fn is_available(&self) -> bool {
+        is_gui_available()
+    }
+
+    /// `rfd`は表示に失敗したことを返さないため、GUIが使えない環境でのみエラーを返します。
+    fn try_show(&self, request: &DialogRequest) -> io::Result<MessageDialogResult> {
+        if !is_gui_available() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "no display is available",
+            ));
+        }
+        Ok(self.show(request))
+    }
+
+    fn name(&self) -> &str {
+        "rfd"
+    }
+}
+
+static BACKEND: RwLock<Option<Arc<dyn DialogBackend>>> = RwLock::new(None);
+
+thread_local! {
+    /// このスレッドでのみ使うバックエンドです。グローバルのバックエンドより優先されます。
+    static THREAD_BACKEND: RefCell<Option<Arc<dyn DialogBackend>>> = const { RefCell::new(None) };
+}
+
+/// ダイアログの表示に使うバックエンドを設定します。
+pub fn set_backend(backend: impl DialogBackend + 'static) {
+    install_backend(Arc::new(backend));
+}
+
+pub(crate) fn install_backend(backend: Arc<dyn DialogBackend>) {
+    *BACKEND.write().unwrap_or_else(PoisonError::into_inner) = Some(backend);
+}
+
+/// バックエンドをデフォルトに戻します。
+pub fn reset_backend() {
+    *BACKEND.write().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+/// このスレッドから表示されるダイアログにのみ使うバックエンドを設定します。
+/// [`set_backend`]で設定されたものより優先され、他のスレッドには影響しないため、並列に実行されるテストでも使えます。
+pub fn set_thread_backend(backend: impl DialogBackend + 'static) {
+    THREAD_BACKEND.set(Some(Arc::new(backend)));
+}
+
+/// このスレッドのバックエンドを取り除き、グローバルのバックエンドを使うように戻します。
+pub fn reset_thread_backend() {
+    THREAD_BACKEND.set(None);
+}
+
+/// 現在のバックエンドを取得します。
+/// [`set_thread_backend`]でこのスレッドのバックエンドが設定されている場合は、それが取得されます。
+/// どちらも設定されていない場合、[`DisplayMode`]に従って[`RfdBackend`]か[`TerminalBackend`]が取得されます。
+pub fn get_backend() -> Arc<dyn DialogBackend> {
+    THREAD_BACKEND
+        .with_borrow(Clone::clone)
+        .or_else(|| {
+            BACKEND
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone()
+        })
+        .unwrap_or_else(default_backend)
+}
+
+fn default_backend() -> Arc<dyn DialogBackend> {
+    match get_display_mode() {
+        DisplayMode::Gui => Arc::new(RfdBackend),
+        DisplayMode::Terminal => Arc::new(TerminalBackend),
+        DisplayMode::Auto if is_gui_available() => Arc::new(RfdBackend),
+        DisplayMode::Auto => Arc::new(TerminalBackend),
+    }
+}
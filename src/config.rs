@@ -0,0 +1,206 @@
+use std::sync::Arc;
+
+use crate::{
+    backend, install_panic_hook, parent, set_coalesce_dialogs, set_crash_report, set_display_mode,
+    set_error_format, set_fatal_policy, set_locale, set_logging, set_main_thread_dispatcher,
+    set_report_buttons, set_show_location, set_terminal_prompt, set_throttle, set_title,
+    set_truncation, CrashReport, DialogBackend, DisplayMode, ErrorFormat, FatalPolicy,
+    MainThreadDispatcher, ParentWindow, ReportButtons, Throttle, Truncation,
+};
+
+/// このクレートの設定をまとめたものです。起動時に[`init`]で一度に設定します。
+/// 指定しなかった項目は、今の設定のままになります。
+///
+/// ```ignore
+/// dialog_unwrapper::init(
+///     DialogConfig::new()
+///         .title("エラー")
+///         .error_format(ErrorFormat::Chain)
+///         .fatal_policy(FatalPolicy::Exit(1))
+///         .panic_hook(true),
+/// );
+/// ```
+#[derive(Default)]
+pub struct DialogConfig {
+    title: Option<String>,
+    locale: Option<String>,
+    truncation: Option<Truncation>,
+    error_format: Option<ErrorFormat>,
+    show_location: Option<bool>,
+    backend: Option<Arc<dyn DialogBackend>>,
+    parent_window: Option<ParentWindow>,
+    display_mode: Option<DisplayMode>,
+    terminal_prompt: Option<bool>,
+    coalesce_dialogs: Option<bool>,
+    main_thread_dispatcher: Option<MainThreadDispatcher>,
+    fatal_policy: Option<FatalPolicy>,
+    report_buttons: Option<ReportButtons>,
+    crash_report: Option<CrashReport>,
+    throttle: Option<Throttle>,
+    logging: Option<bool>,
+    panic_hook: bool,
+}
+
+impl DialogConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// [`set_title`]を参照してください。
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// [`set_locale`]を参照してください。
+    pub fn locale(mut self, locale: impl Into<String>) -> Self {
+        self.locale = Some(locale.into());
+        self
+    }
+
+    /// [`set_truncation`]を参照してください。
+    pub fn truncation(mut self, truncation: Truncation) -> Self {
+        self.truncation = Some(truncation);
+        self
+    }
+
+    /// [`set_error_format`]を参照してください。
+    pub fn error_format(mut self, format: ErrorFormat) -> Self {
+        self.error_format = Some(format);
+        self
+    }
+
+    /// [`set_show_location`]を参照してください。
+    pub fn show_location(mut self, show: bool) -> Self {
+        self.show_location = Some(show);
+        self
+    }
+
+    /// [`set_backend`](crate::set_backend)を参照してください。
+    pub fn backend(mut self, backend: impl DialogBackend + 'static) -> Self {
+        self.backend = Some(Arc::new(backend));
+        self
+    }
+
+    /// [`set_parent_window`](crate::set_parent_window)を参照してください。
+    pub fn parent_window(mut self, parent: ParentWindow) -> Self {
+        self.parent_window = Some(parent);
+        self
+    }
+
+    /// [`set_display_mode`]を参照してください。
+    pub fn display_mode(mut self, mode: DisplayMode) -> Self {
+        self.display_mode = Some(mode);
+        self
+    }
+
+    /// [`set_terminal_prompt`]を参照してください。
+    pub fn terminal_prompt(mut self, prompt: bool) -> Self {
+        self.terminal_prompt = Some(prompt);
+        self
+    }
+
+    /// [`set_coalesce_dialogs`]を参照してください。
+    pub fn coalesce_dialogs(mut self, coalesce: bool) -> Self {
+        self.coalesce_dialogs = Some(coalesce);
+        self
+    }
+
+    /// [`set_main_thread_dispatcher`]を参照してください。
+    pub fn main_thread_dispatcher(mut self, dispatcher: MainThreadDispatcher) -> Self {
+        self.main_thread_dispatcher = Some(dispatcher);
+        self
+    }
+
+    /// [`set_fatal_policy`]を参照してください。
+    pub fn fatal_policy(mut self, policy: FatalPolicy) -> Self {
+        self.fatal_policy = Some(policy);
+        self
+    }
+
+    /// [`set_report_buttons`]を参照してください。
+    pub fn report_buttons(mut self, buttons: ReportButtons) -> Self {
+        self.report_buttons = Some(buttons);
+        self
+    }
+
+    /// [`set_crash_report`]を参照してください。
+    pub fn crash_report(mut self, report: CrashReport) -> Self {
+        self.crash_report = Some(report);
+        self
+    }
+
+    /// [`set_throttle`]を参照してください。
+    pub fn throttle(mut self, throttle: Throttle) -> Self {
+        self.throttle = Some(throttle);
+        self
+    }
+
+    /// [`set_logging`]を参照してください。
+    pub fn logging(mut self, enabled: bool) -> Self {
+        self.logging = Some(enabled);
+        self
+    }
+
+    /// `true`の場合、[`install_panic_hook`]を呼び出します。
+    pub fn panic_hook(mut self, panic_hook: bool) -> Self {
+        self.panic_hook = panic_hook;
+        self
+    }
+}
+
+/// 設定をまとめて反映します。アプリの起動時に一度だけ呼び出してください。
+/// 起動後に一部の設定を変えたい場合は、`set_title`などの個別の関数を使えます。
+pub fn init(config: DialogConfig) {
+    if let Some(title) = config.title {
+        set_title(title);
+    }
+    if let Some(locale) = config.locale {
+        set_locale(locale);
+    }
+    if let Some(truncation) = config.truncation {
+        set_truncation(truncation);
+    }
+    if let Some(format) = config.error_format {
+        set_error_format(format);
+    }
+    if let Some(show) = config.show_location {
+        set_show_location(show);
+    }
+    if let Some(backend) = config.backend {
+        backend::install_backend(backend);
+    }
+    if let Some(parent) = config.parent_window {
+        parent::install_parent_window(parent);
+    }
+    if let Some(mode) = config.display_mode {
+        set_display_mode(mode);
+    }
+    if let Some(prompt) = config.terminal_prompt {
+        set_terminal_prompt(prompt);
+    }
+    if let Some(coalesce) = config.coalesce_dialogs {
+        set_coalesce_dialogs(coalesce);
+    }
+    if let Some(dispatcher) = config.main_thread_dispatcher {
+        set_main_thread_dispatcher(Some(dispatcher));
+    }
+    if let Some(policy) = config.fatal_policy {
+        set_fatal_policy(policy);
+    }
+    if let Some(buttons) = config.report_buttons {
+        set_report_buttons(buttons);
+    }
+    if let Some(report) = config.crash_report {
+        set_crash_report(Some(report));
+    }
+    if let Some(throttle) = config.throttle {
+        set_throttle(throttle);
+    }
+    if let Some(enabled) = config.logging {
+        set_logging(enabled);
+    }
+    if config.panic_hook {
+        install_panic_hook();
+    }
+}
@@ -0,0 +1,178 @@
+use std::{
+    backtrace::Backtrace,
+    env,
+    fmt::Write as _,
+    fs::{self, OpenOptions},
+    io::{self, Write as _},
+    path::PathBuf,
+    process,
+    sync::{PoisonError, RwLock},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{get_message, logging, DialogRequest, MessageKey};
+
+/// 致命的なエラーの時に書き出すクラッシュレポートの設定です。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CrashReport {
+    /// レポートを書き出すフォルダです。存在しない場合は作成されます。
+    pub dir: PathBuf,
+    /// レポートに書き込むアプリの名前とバージョンです。
+    /// `env!("CARGO_PKG_NAME")`と`env!("CARGO_PKG_VERSION")`を渡すと良いです。
+    pub app: Option<(String, String)>,
+}
+
+impl CrashReport {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            app: None,
+        }
+    }
+
+    pub fn app(mut self, name: impl Into<String>, version: impl Into<String>) -> Self {
+        self.app = Some((name.into(), version.into()));
+        self
+    }
+}
+
+static CRASH_REPORT: RwLock<Option<CrashReport>> = RwLock::new(None);
+
+/// クラッシュレポートの設定をします。`None`の場合は書き出しません。これがデフォルトです。
+pub fn set_crash_report(report: Option<CrashReport>) {
+    *CRASH_REPORT.write().unwrap_or_else(PoisonError::into_inner) = report;
+}
+
+/// 現在のクラッシュレポートの設定を取得します。
+pub fn get_crash_report() -> Option<CrashReport> {
+    CRASH_REPORT
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// UNIX時間を、UTCの年月日と時分秒に変換します。
+pub(crate) fn utc(secs: u64) -> (i64, u64, u64, u64, u64, u64) {
+    let (days, rest) = ((secs / 86400) as i64, secs % 86400);
+
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097) as u64;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe as i64 + era * 400 + i64::from(month <= 2);
+
+    (year, month, day, rest / 3600, rest / 60 % 60, rest % 60)
+}
+
+/// クラッシュレポートを書き出し、そのパスを返します。
+/// クラッシュレポートが設定されていない場合は`Ok(None)`を返します。
+pub fn write_crash_report(title: &str, error: &str) -> io::Result<Option<PathBuf>> {
+    let Some(config) = get_crash_report() else {
+        return Ok(None);
+    };
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let (year, month, day, hour, minute, second) = utc(now.as_secs());
+
+    let mut report = String::new();
+    let _ = writeln!(report, "Title: {}", title);
+    let _ = writeln!(
+        report,
+        "Time: {:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year, month, day, hour, minute, second
+    );
+    if let Some((name, version)) = &config.app {
+        let _ = writeln!(report, "Application: {} {}", name, version);
+    }
+    let _ = writeln!(
+        report,
+        "{}: {}",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    );
+    let _ = writeln!(report, "OS: {} ({})", env::consts::OS, env::consts::ARCH);
+    let _ = writeln!(
+        report,
+        "Arguments: {:?}",
+        env::args_os().collect::<Vec<_>>()
+    );
+    let _ = write!(
+        report,
+        "\n{}\n\nBacktrace:\n{}\n",
+        error,
+        Backtrace::force_capture()
+    );
+
+    fs::create_dir_all(&config.dir)?;
+    let stem = format!(
+        "crash-{:04}{:02}{:02}-{:02}{:02}{:02}-{}",
+        year,
+        month,
+        day,
+        hour,
+        minute,
+        second,
+        process::id()
+    );
+    // 同じ秒に複数のスレッドで致命的なエラーが起きても上書きしないように、既にある場合は番号を付けます。
+    for n in 0.. {
+        let path = match n {
+            0 => config.dir.join(format!("{}.txt", stem)),
+            n => config.dir.join(format!("{}-{}.txt", stem, n)),
+        };
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                file.write_all(report.as_bytes())?;
+                return Ok(Some(path));
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    unreachable!()
+}
+
+/// クラッシュレポートを書き出し、そのパスをダイアログの説明に書き加えます。
+/// 書き出せなかった場合は、その理由をログに出力し、説明に書き加えます。
+pub(crate) fn attach(request: &mut DialogRequest, error: &str) {
+    let (key, detail) = match write_crash_report(&request.title, error) {
+        Ok(None) => return,
+        Ok(Some(path)) => (MessageKey::CrashReportSaved, path.display().to_string()),
+        Err(e) => {
+            logging::log_crash_report_failure(&e);
+            (MessageKey::CrashReportFailed, e.to_string())
+        }
+    };
+    let _ = write!(request.description, "\n\n{}\n{}", get_message(key), detail);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_are_not_overwritten() {
+        let dir = env::temp_dir().join(format!("dialog-unwrapper-crash-{}", process::id()));
+        set_crash_report(Some(CrashReport::new(&dir)));
+
+        let first = write_crash_report("First", "first error").unwrap().unwrap();
+        let second = write_crash_report("Second", "second error")
+            .unwrap()
+            .unwrap();
+        set_crash_report(None);
+
+        assert_ne!(first, second);
+        assert!(fs::read_to_string(&first).unwrap().contains("first error"));
+        assert!(fs::read_to_string(&second)
+            .unwrap()
+            .contains("second error"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
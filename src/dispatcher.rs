@@ -0,0 +1,305 @@
+use std::{
+    cell::Cell,
+    collections::BTreeSet,
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver, Sender},
+        Condvar, Mutex, MutexGuard, OnceLock, PoisonError,
+    },
+    task::{Context, Poll, Waker},
+    thread,
+};
+
+use rfd::MessageDialogResult;
+
+use crate::{main_thread, DialogFuture, DialogRequest, RfdBackend, Severity};
+
+static COALESCE: AtomicBool = AtomicBool::new(false);
+
+/// 前のダイアログが閉じられるのを待っている間に溜まったエラーを、一つのダイアログにまとめて表示するかどうかを設定します。
+/// まとめられるのは閉じられるのを待たないダイアログのみで、デフォルトではまとめません。
+pub fn set_coalesce_dialogs(coalesce: bool) {
+    COALESCE.store(coalesce, Ordering::SeqCst);
+}
+
+/// 溜まったダイアログをまとめて表示するかどうかを取得します。
+pub fn is_coalescing_dialogs() -> bool {
+    COALESCE.load(Ordering::SeqCst)
+}
+
+/// ダイアログが閉じられた時に、押されたボタンを呼び出し元に返すためのものです。
+/// 返す前に捨てられた場合は、デフォルトの結果を返します。
+struct Reply(Option<Box<dyn FnOnce(MessageDialogResult) + Send>>);
+
+impl Reply {
+    fn new(f: impl FnOnce(MessageDialogResult) + Send + 'static) -> Self {
+        Self(Some(Box::new(f)))
+    }
+
+    fn send(mut self, result: MessageDialogResult) {
+        if let Some(f) = self.0.take() {
+            f(result);
+        }
+    }
+}
+
+impl Drop for Reply {
+    fn drop(&mut self) {
+        if let Some(f) = self.0.take() {
+            f(MessageDialogResult::default());
+        }
+    }
+}
+
+struct Job {
+    request: DialogRequest,
+    reply: Option<Reply>,
+}
+
+thread_local! {
+    /// このスレッドがダイアログを表示する専用のスレッドかどうかです。
+    static ON_DISPATCHER: Cell<bool> = const { Cell::new(false) };
+}
+
+/// 今のスレッドが、ダイアログを表示する専用のスレッドかどうかを取得します。
+pub(crate) fn is_dispatcher_thread() -> bool {
+    ON_DISPATCHER.get()
+}
+
+/// ダイアログを表示する専用のスレッドのキューを取得します。
+/// スレッドは最初に使われた時に起動され、送られたダイアログを一つずつ順番に表示します。
+fn queue() -> &'static Sender<Job> {
+    static QUEUE: OnceLock<Sender<Job>> = OnceLock::new();
+
+    QUEUE.get_or_init(|| {
+        let (sender, receiver) = channel();
+        thread::Builder::new()
+            .name(String::from("dialog-unwrapper-ui"))
+            .spawn(move || {
+                ON_DISPATCHER.set(true);
+                run(receiver);
+            })
+            .expect("failed to spawn the dialog thread");
+        sender
+    })
+}
+
+fn run(receiver: Receiver<Job>) {
+    let mut pending = None;
+    while let Some(job) = pending.take().or_else(|| receiver.recv().ok()) {
+        let mut jobs = vec![job];
+        if is_coalescing_dialogs() && !jobs[0].request.blocking {
+            while let Ok(next) = receiver.try_recv() {
+                if next.request.blocking {
+                    pending = Some(next);
+                    break;
+                }
+                jobs.push(next);
+            }
+        }
+
+        let turn = Turn::take().wait();
+        // `rfd`の中でパニックしても専用のスレッドが終了しないように、閉じられたものとして扱います。
+        let result = panic::catch_unwind(AssertUnwindSafe(|| match &jobs[..] {
+            [job] => show_native(&job.request),
+            jobs => show_native(&combine(jobs)),
+        }))
+        .unwrap_or_default();
+        drop(turn);
+        for reply in jobs.into_iter().filter_map(|job| job.reply) {
+            reply.send(result.clone());
+        }
+    }
+}
+
+/// 複数のダイアログを一つにまとめます。重要度は最も高いものになります。
+fn combine(jobs: &[Job]) -> DialogRequest {
+    let requests: Vec<_> = jobs.iter().map(|job| &job.request).collect();
+    let join = |f: fn(&DialogRequest) -> &str| {
+        requests
+            .iter()
+            .map(|request| format!("{}\n{}", request.title, f(request)))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    };
+    let severity = [Severity::Error, Severity::Warning]
+        .into_iter()
+        .find(|&severity| requests.iter().any(|request| request.severity == severity))
+        .unwrap_or(Severity::Info);
+
+    DialogRequest {
+        text: join(|request| &request.text),
+        description: join(|request| &request.description),
+        severity,
+        ..requests[0].clone()
+    }
+}
+
+/// ネイティブのダイアログを表示します。
+/// [`MainThreadDispatcher`](crate::MainThreadDispatcher)が設定されている場合は、メインスレッドで表示します。
+fn show_native(request: &DialogRequest) -> MessageDialogResult {
+    let request = request.clone();
+    main_thread::on_main_thread(move || RfdBackend::show_blocking(&request)).unwrap_or_default()
+}
+
+/// 専用のスレッドでダイアログを表示します。
+/// 閉じられるのを待つ場合は、キューの前にあるダイアログと自分のダイアログが閉じられるまでブロックします。
+/// ただし、次の場合はキューを通さずにこの場で表示します。
+///
+/// - [`MainThreadDispatcher`](crate::MainThreadDispatcher)のメインスレッドで、閉じられるのを待つダイアログ
+/// - フックが設定されていない時に、macOSのメインスレッドで起きたエラーのダイアログ
+pub(crate) fn show(request: &DialogRequest) -> MessageDialogResult {
+    if ON_DISPATCHER.get() {
+        return show_native(request);
+    }
+
+    let in_place = match main_thread::get_main_thread_dispatcher() {
+        // メインスレッドが専用のスレッドを待つと、専用のスレッドがフックでメインスレッドに送ったダイアログが
+        // 実行されずに待ち続けることになるため、この場で表示します。
+        // 閉じられるのを待たないものは、フックを通してイベントループで表示されます。
+        Some(dispatcher) => dispatcher.is_main_thread() && request.blocking,
+        // macOSの`rfd`はメインスレッドで表示しようとするため、メインスレッドが待っているとデッドロックし、
+        // 待っていなくてもイベントループが無いと表示できないため、どちらもこの場で表示します。
+        None => main_thread::is_macos_main_thread(),
+    };
+    if in_place {
+        return RfdBackend::show_blocking(request);
+    }
+
+    if !request.blocking {
+        let job = Job {
+            request: request.clone(),
+            reply: None,
+        };
+        if let Err(e) = queue().send(job) {
+            // 専用のスレッドが何らかの理由で終了している場合は、この場で表示します。
+            return show_native(&e.0.request);
+        }
+        return MessageDialogResult::default();
+    }
+
+    let (sender, receiver) = channel();
+    let job = Job {
+        request: request.clone(),
+        reply: Some(Reply::new(move |result| {
+            let _ = sender.send(result);
+        })),
+    };
+    if let Err(e) = queue().send(job) {
+        return show_native(&e.0.request);
+    }
+    receiver.recv().unwrap_or_default()
+}
+
+/// ダイアログを一つずつ表示するための順番待ちです。
+/// 専用のスレッドと[`show_async`]のダイアログは、受け取った番号の順に表示されます。
+struct Turns {
+    next: u64,
+    serving: u64,
+    /// 順番が来る前に捨てられた番号です。順番が来たら飛ばします。
+    abandoned: BTreeSet<u64>,
+    wakers: Vec<Waker>,
+}
+
+static TURNS: Mutex<Turns> = Mutex::new(Turns {
+    next: 0,
+    serving: 0,
+    abandoned: BTreeSet::new(),
+    wakers: Vec::new(),
+});
+static TURN_CHANGED: Condvar = Condvar::new();
+
+fn lock_turns() -> MutexGuard<'static, Turns> {
+    TURNS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// 順番の番号です。ドロップされると、次の番号に順番が回ります。
+struct Turn(u64);
+
+impl Turn {
+    fn take() -> Self {
+        let mut turns = lock_turns();
+        turns.next += 1;
+        Self(turns.next - 1)
+    }
+
+    /// 順番が来るまでこのスレッドをブロックします。
+    fn wait(self) -> Self {
+        let mut turns = lock_turns();
+        while turns.serving != self.0 {
+            turns = TURN_CHANGED
+                .wait(turns)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        self
+    }
+}
+
+impl Drop for Turn {
+    fn drop(&mut self) {
+        let mut guard = lock_turns();
+        let turns = &mut *guard;
+        if turns.serving == self.0 {
+            turns.serving += 1;
+            while turns.abandoned.remove(&turns.serving) {
+                turns.serving += 1;
+            }
+        } else {
+            turns.abandoned.insert(self.0);
+        }
+        for waker in turns.wakers.drain(..) {
+            waker.wake();
+        }
+        TURN_CHANGED.notify_all();
+    }
+}
+
+/// 順番が来るまで待つFutureです。
+struct WaitTurn(Option<Turn>);
+
+impl Future for WaitTurn {
+    type Output = Turn;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let ticket = self.0.as_ref().expect("polled after completion").0;
+        let mut turns = lock_turns();
+        if turns.serving == ticket {
+            drop(turns);
+            Poll::Ready(self.0.take().unwrap())
+        } else {
+            turns.wakers.push(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+/// [`show`]の非同期版です。`rfd`の非同期のダイアログを使い、呼び出し元のスレッドをブロックせずに閉じられるのを待ちます。
+/// 順番が来るまでは、専用のスレッドのダイアログと同じ順番待ちで待ちます。
+pub(crate) fn show_async(request: DialogRequest) -> DialogFuture {
+    let turn = WaitTurn(Some(Turn::take()));
+    Box::pin(async move {
+        let _turn = turn.await;
+        RfdBackend::show_async_native(&request).await
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn abandoned_turns_are_skipped() {
+        let first = Turn::take();
+        let abandoned = Turn::take();
+        let last = Turn::take();
+
+        drop(abandoned);
+        drop(first);
+
+        let waiter = thread::spawn(move || drop(last.wait()));
+        waiter.join().unwrap();
+    }
+}
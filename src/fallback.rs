@@ -0,0 +1,183 @@
+use std::{
+    fmt::{self, Debug},
+    future::ready,
+    io,
+    path::PathBuf,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use rfd::MessageDialogResult;
+
+use crate::{
+    logging, DialogBackend, DialogFuture, DialogRequest, LogFileBackend, RfdBackend,
+    SubprocessBackend, TerminalBackend,
+};
+
+/// 複数のバックエンドを順番に試し、最初に表示できたものでダイアログを表示するバックエンドです。
+/// 使えない環境のバックエンドや、表示に失敗したバックエンドは飛ばされます。
+/// クローンしたものは、最後に使われたバックエンドの記録を共有します。
+///
+/// [`RfdBackend`]は表示に失敗したことを返さないため、ディスプレイが無い場合にしか次のバックエンドに進みません。
+/// ディスプレイがあるのにダイアログが表示されなかった場合は、そのまま閉じられたものとして扱われます。
+/// また、非同期で表示する場合は失敗を検出できないため、使える環境の最初のバックエンドで表示します。
+///
+/// ```ignore
+/// dialog_unwrapper::set_backend(FallbackBackend::standard("errors.log"));
+/// ```
+#[derive(Clone, Default)]
+pub struct FallbackBackend {
+    backends: Vec<Arc<dyn DialogBackend>>,
+    last_used: Arc<Mutex<Option<String>>>,
+}
+
+impl FallbackBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `rfd`、`zenity`、`kdialog`、ターミナル、`log_file`へのファイルの順に試すバックエンドを作ります。
+    pub fn standard(log_file: impl Into<PathBuf>) -> Self {
+        Self::new()
+            .then(RfdBackend)
+            .then(SubprocessBackend::zenity())
+            .then(SubprocessBackend::kdialog())
+            .then(TerminalBackend)
+            .then(LogFileBackend::new(log_file))
+    }
+
+    /// 試すバックエンドを最後に追加します。
+    pub fn then(mut self, backend: impl DialogBackend + 'static) -> Self {
+        self.backends.push(Arc::new(backend));
+        self
+    }
+
+    fn record(&self, name: &str) {
+        *self
+            .last_used
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(name.to_owned());
+    }
+
+    /// 最後にダイアログを表示できたバックエンドの名前を取得します。
+    pub fn last_used(&self) -> Option<String> {
+        self.last_used
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+fn unavailable() -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, "no dialog backend is available")
+}
+
+impl Debug for FallbackBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<_> = self.backends.iter().map(|backend| backend.name()).collect();
+        f.debug_struct("FallbackBackend")
+            .field("backends", &names)
+            .field("last_used", &self.last_used())
+            .finish()
+    }
+}
+
+impl DialogBackend for FallbackBackend {
+    fn show(&self, request: &DialogRequest) -> MessageDialogResult {
+        self.try_show(request).unwrap_or_default()
+    }
+
+    /// 使える環境の最初のバックエンドの非同期版で表示します。
+    fn show_async(&self, request: DialogRequest) -> DialogFuture {
+        let Some(backend) = self.backends.iter().find(|backend| backend.is_available()) else {
+            logging::log_backend(self.name(), &Err(unavailable()));
+            return Box::pin(ready(MessageDialogResult::default()));
+        };
+
+        self.record(backend.name());
+        backend.show_async(request)
+    }
+
+    fn is_available(&self) -> bool {
+        self.backends.iter().any(|backend| backend.is_available())
+    }
+
+    fn try_show(&self, request: &DialogRequest) -> io::Result<MessageDialogResult> {
+        let mut last_error = None;
+        for backend in self
+            .backends
+            .iter()
+            .filter(|backend| backend.is_available())
+        {
+            let result = backend.try_show(request);
+            logging::log_backend(backend.name(), &result);
+            match result {
+                Ok(result) => {
+                    self.record(backend.name());
+                    return Ok(result);
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        Err(last_error.unwrap_or_else(unavailable))
+    }
+
+    fn name(&self) -> &str {
+        "fallback"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::task::{Context, Poll, Waker};
+
+    use super::*;
+    use crate::{testing::RecordingBackend, Severity};
+
+    struct Unavailable;
+
+    impl DialogBackend for Unavailable {
+        fn show(&self, _: &DialogRequest) -> MessageDialogResult {
+            unreachable!("an unavailable backend must not be used")
+        }
+
+        fn is_available(&self) -> bool {
+            false
+        }
+
+        fn name(&self) -> &str {
+            "unavailable"
+        }
+    }
+
+    #[test]
+    fn show_async_uses_the_first_available_backend() {
+        let recorder = RecordingBackend::new();
+        recorder.push_response(MessageDialogResult::Yes);
+        let backend = FallbackBackend::new()
+            .then(Unavailable)
+            .then(recorder.clone());
+        let request = DialogRequest {
+            title: String::from("Error"),
+            text: String::from("failed"),
+            description: String::from("failed"),
+            severity: Severity::Error,
+            buttons: rfd::MessageButtons::Ok,
+            blocking: true,
+            location: None,
+            parent: None,
+        };
+
+        let mut dialog = backend.show_async(request);
+        let result = dialog
+            .as_mut()
+            .poll(&mut Context::from_waker(Waker::noop()));
+
+        assert_eq!(result, Poll::Ready(MessageDialogResult::Yes));
+        recorder.assert_last("Error", "failed");
+        assert_eq!(
+            backend.last_used().as_deref(),
+            Some(std::any::type_name::<RecordingBackend>())
+        );
+    }
+}
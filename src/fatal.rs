@@ -0,0 +1,54 @@
+use std::{
+    fmt::{self, Debug},
+    sync::{Arc, PoisonError, RwLock},
+};
+
+/// [`FatalPolicy::Callback`]で呼び出される関数です。
+pub type FatalCallback = Arc<dyn Fn(&str, &str) + Send + Sync>;
+
+/// `unwrap_or_dialog`などでダイアログを表示した後に、どのようにプログラムを終了するかです。
+#[derive(Clone, Default)]
+pub enum FatalPolicy {
+    /// `panic!`します。これがデフォルトです。
+    #[default]
+    Panic,
+    /// 指定された終了コードで`std::process::exit`します。
+    Exit(i32),
+    /// `std::process::abort`します。
+    Abort,
+    /// タイトルとエラーの全文を渡して関数を呼び出します。
+    /// 関数から処理が戻ってきた場合は`panic!`します。
+    Callback(FatalCallback),
+}
+
+impl FatalPolicy {
+    pub fn callback(callback: impl Fn(&str, &str) + Send + Sync + 'static) -> Self {
+        Self::Callback(Arc::new(callback))
+    }
+}
+
+impl Debug for FatalPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Panic => f.write_str("Panic"),
+            Self::Exit(code) => f.debug_tuple("Exit").field(code).finish(),
+            Self::Abort => f.write_str("Abort"),
+            Self::Callback(_) => f.write_str("Callback(..)"),
+        }
+    }
+}
+
+static FATAL_POLICY: RwLock<FatalPolicy> = RwLock::new(FatalPolicy::Panic);
+
+/// ダイアログを表示した後の終了方法を設定します。
+pub fn set_fatal_policy(policy: FatalPolicy) {
+    *FATAL_POLICY.write().unwrap_or_else(PoisonError::into_inner) = policy;
+}
+
+/// 現在の終了方法を取得します。
+pub fn get_fatal_policy() -> FatalPolicy {
+    FATAL_POLICY
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
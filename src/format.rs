@@ -0,0 +1,228 @@
+use std::{
+    any::Any,
+    error::Error as StdError,
+    fmt::{self, Debug, Display, Write as _},
+    iter::successors,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        PoisonError, RwLock,
+    },
+};
+
+use crate::{get_message, MessageKey};
+
+/// ダイアログに表示するエラーの文章の書式です。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ErrorFormat {
+    /// `{:?}`で整形します。anyhowの場合は"Caused by:"やバックトレースも含まれます。
+    Debug,
+    /// `{}`で整形します。anyhowの場合は一番外側のコンテキストのみになります。
+    Display,
+    /// `{:#}`で整形します。anyhowの場合は原因が`: `で繋げられます。
+    AlternateDisplay,
+    /// 一番外側のコンテキストを見出しに、その原因を"Caused by:"の下に箇条書きにします。
+    #[default]
+    Chain,
+}
+
+static ERROR_FORMAT: RwLock<ErrorFormat> = RwLock::new(ErrorFormat::Chain);
+
+/// ダイアログに表示するエラーの書式を設定します。
+/// [`WithSources`]で変換したエラーに使われ、変換していないエラーは`{:?}`で整形されます。
+pub fn set_error_format(format: ErrorFormat) {
+    *ERROR_FORMAT.write().unwrap_or_else(PoisonError::into_inner) = format;
+}
+
+/// 現在のエラーの書式を取得します。
+pub fn get_error_format() -> ErrorFormat {
+    *ERROR_FORMAT.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+static SHOW_LOCATION: AtomicBool = AtomicBool::new(false);
+
+/// `unwrap_or_dialog`などが呼び出された場所を、ダイアログの詳細に書き加えるかどうかを設定します。
+/// デフォルトでは書き加えません。ログとパニックのメッセージには常に含まれます。
+pub fn set_show_location(show: bool) {
+    SHOW_LOCATION.store(show, Ordering::SeqCst);
+}
+
+/// 呼び出された場所をダイアログに書き加えるかどうかを取得します。
+pub fn is_location_shown() -> bool {
+    SHOW_LOCATION.load(Ordering::SeqCst)
+}
+
+fn render_chain<'a>(mut chain: impl Iterator<Item = &'a (dyn StdError + 'static)>) -> String {
+    let mut text = chain.next().map(ToString::to_string).unwrap_or_default();
+
+    let mut causes = chain.peekable();
+    if causes.peek().is_some() {
+        let _ = write!(text, "\n\n{}", get_message(MessageKey::CausedBy));
+        for cause in causes {
+            let _ = write!(text, "\n  • {}", cause);
+        }
+    }
+
+    text
+}
+
+/// `std::error::Error`を指定された書式で整形します。
+/// [`ErrorFormat::Chain`]では`source()`を辿って原因を列挙します。
+pub fn format_std_error(e: &(dyn StdError + 'static), format: ErrorFormat) -> String {
+    match format {
+        ErrorFormat::Debug => format!("{:?}", e),
+        ErrorFormat::Display => format!("{}", e),
+        ErrorFormat::AlternateDisplay => format!("{:#}", e),
+        ErrorFormat::Chain => render_chain(successors(Some(e), |&e| e.source())),
+    }
+}
+
+/// エラーを、[`ErrorFormat`]に従って`source()`を辿って整形されるエラーに変換します。
+/// `anyhow::Error`や、`ParseIntError`や`thiserror`で作ったエラーなどの`std::error::Error`、`String`を変換できます。
+/// 変換しないエラーは、書式に関わらず`{:?}`で整形されます。
+///
+/// ```ignore
+/// let port: u16 = input.parse().with_sources().unwrap_or_dialog();
+/// ```
+pub trait WithSources<T> {
+    fn with_sources(self) -> Result<T, ErrorSources>;
+}
+
+impl<T, E: Into<Box<dyn StdError>>> WithSources<T> for Result<T, E> {
+    fn with_sources(self) -> Result<T, ErrorSources> {
+        match self {
+            Ok(value) => Ok(value),
+            Err(e) => Err(ErrorSources(e.into())),
+        }
+    }
+}
+
+/// [`WithSources`]で変換されたエラーです。
+/// `{:?}`では、[`set_error_format`]で設定された書式で整形されます。
+pub struct ErrorSources(Box<dyn StdError>);
+
+impl ErrorSources {
+    /// 指定された書式で整形します。
+    pub fn format(&self, format: ErrorFormat) -> String {
+        format_std_error(self.0.as_ref(), format)
+    }
+
+    pub fn into_inner(self) -> Box<dyn StdError> {
+        self.0
+    }
+}
+
+impl Debug for ErrorSources {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.format(get_error_format()))
+    }
+}
+
+impl Display for ErrorSources {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+fn as_std_error(e: &dyn Any) -> Option<&(dyn StdError + 'static)> {
+    if let Some(e) = e.downcast_ref::<Box<dyn StdError + Send + Sync>>() {
+        Some(e.as_ref())
+    } else if let Some(e) = e.downcast_ref::<Box<dyn StdError + Send>>() {
+        Some(e.as_ref())
+    } else if let Some(e) = e.downcast_ref::<Box<dyn StdError>>() {
+        Some(e.as_ref())
+    } else if let Some(e) = e.downcast_ref::<std::io::Error>() {
+        Some(e)
+    } else if let Some(e) = e.downcast_ref::<ErrorSources>() {
+        Some(e.0.as_ref())
+    } else {
+        None
+    }
+}
+
+/// エラーを指定された書式で整形します。
+/// `anyhow::Error`と`Box<dyn std::error::Error>`、`std::io::Error`、[`ErrorSources`]以外のエラーは、書式に関わらず`{:?}`で整形されます。
+/// `unwrap_or_dialog`などは`'static`でないエラーも受け取るため、これを使わずに`{:?}`で整形します。
+pub fn format_error<E: Debug + 'static>(e: &E, format: ErrorFormat) -> String {
+    let any = e as &dyn Any;
+
+    if let Some(e) = any.downcast_ref::<anyhow::Error>() {
+        match format {
+            ErrorFormat::Debug => format!("{:?}", e),
+            ErrorFormat::Display => format!("{}", e),
+            ErrorFormat::AlternateDisplay => format!("{:#}", e),
+            ErrorFormat::Chain => render_chain(e.chain()),
+        }
+    } else if let Some(e) = as_std_error(any) {
+        format_std_error(e, format)
+    } else {
+        format!("{:?}", e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+
+    use anyhow::anyhow;
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct Outer(std::num::ParseIntError);
+
+    impl fmt::Display for Outer {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("invalid port")
+        }
+    }
+
+    impl StdError for Outer {
+        fn source(&self) -> Option<&(dyn StdError + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    fn outer() -> Outer {
+        Outer("x".parse::<u16>().unwrap_err())
+    }
+
+    #[test]
+    fn with_sources_walks_the_chain() {
+        let e = Err::<(), _>(outer()).with_sources().unwrap_err();
+
+        let text = e.format(ErrorFormat::Chain);
+
+        assert!(text.starts_with("invalid port\n\n"));
+        assert!(text.ends_with("\n  • invalid digit found in string"));
+        assert_eq!(e.format(ErrorFormat::Display), "invalid port");
+        assert_eq!(format_error(&e, ErrorFormat::Display), "invalid port");
+    }
+
+    #[test]
+    fn with_sources_accepts_anyhow() {
+        let e = Err::<(), _>(anyhow!("inner").context("outer"))
+            .with_sources()
+            .unwrap_err();
+
+        assert_eq!(e.format(ErrorFormat::Display), "outer");
+        assert!(e.format(ErrorFormat::Chain).ends_with("\n  • inner"));
+    }
+
+    #[test]
+    fn std_error_without_conversion_uses_debug() {
+        let e = outer();
+
+        assert_eq!(format_error(&e, ErrorFormat::Display), format!("{:?}", e));
+    }
+
+    #[test]
+    fn anyhow_chain() {
+        let e = anyhow!("inner").context("outer");
+
+        assert_eq!(
+            format_error(&e, ErrorFormat::AlternateDisplay),
+            "outer: inner"
+        );
+        assert!(format_error(&e, ErrorFormat::Chain).ends_with("\n  • inner"));
+    }
+}
@@ -1,24 +1,75 @@
 use std::{
     fmt::{Debug, Display},
-    sync::{
-        atomic::{AtomicUsize, Ordering},
-        OnceLock,
-    },
+    panic::Location,
+    process,
 };
 
 use anyhow::Error;
 
+pub use anyhow;
+pub use raw_window_handle;
+use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
 pub use rfd;
-use rfd::{AsyncMessageDialog, MessageDialog};
+use rfd::MessageButtons;
 
-/// ダイアログのデフォルトのタイトルです。
-pub static DEFAULT_TITLE: OnceLock<String> = OnceLock::new();
+mod asynchronous;
+mod backend;
+mod config;
+mod crash_report;
+mod dispatcher;
+mod fallback;
+mod fatal;
+mod format;
+mod locale;
+mod log_file;
+mod logging;
+mod main_thread;
+mod option;
+mod panic_hook;
+mod parent;
+mod report;
+mod retry;
+mod subprocess;
+mod terminal;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+mod throttle;
+mod title;
+mod truncate;
 
-/// `get_title`を使って予期せぬエラーのタイトルを取得します。
-/// もし設定されていない場合、デフォルトの"Unexpected Error"が取得されます。
-pub fn get_title() -> &'static str {
-    &DEFAULT_TITLE.get_or_init(|| String::from("Unexpected Error"))
-}
+pub use asynchronous::{show_error_dialog_async, AsyncErrorDialogUnwrapper};
+pub use backend::{
+    get_backend, reset_backend, reset_thread_backend, set_backend, set_thread_backend,
+    DialogBackend, DialogFuture, DialogRequest, RfdBackend, Severity,
+};
+pub use config::{init, DialogConfig};
+pub use crash_report::{get_crash_report, set_crash_report, write_crash_report, CrashReport};
+pub use dispatcher::{is_coalescing_dialogs, set_coalesce_dialogs};
+pub use fallback::FallbackBackend;
+pub use fatal::{get_fatal_policy, set_fatal_policy, FatalCallback, FatalPolicy};
+pub use format::{
+    format_error, format_std_error, get_error_format, is_location_shown, set_error_format,
+    set_show_location, ErrorFormat, ErrorSources, WithSources,
+};
+pub use locale::{get_locale, get_message, register_locale, set_locale, set_message, MessageKey};
+pub use log_file::LogFileBackend;
+pub use logging::{is_logging_enabled, set_logging};
+pub use main_thread::{
+    get_main_thread_dispatcher, set_main_thread_dispatcher, DialogTask, MainThreadDispatcher,
+};
+pub use option::OptionDialogUnwrapper;
+pub use panic_hook::install_panic_hook;
+pub use parent::{clear_parent_window, get_parent_window, set_parent_window, ParentWindow};
+pub use report::{get_report_buttons, set_report_buttons, ReportButtons};
+pub use retry::{retry_or_dialog, retry_or_ignore_dialog, Retry};
+pub use subprocess::{DialogProgram, SubprocessBackend};
+pub use terminal::{
+    get_display_mode, is_gui_available, is_terminal_prompt_enabled, set_display_mode,
+    set_terminal_prompt, DisplayMode, TerminalBackend,
+};
+pub use throttle::{get_throttle, set_throttle, with_dedup_key, Throttle};
+pub use title::{get_severity_title, get_title, set_title, with_title};
+pub use truncate::{get_truncation, set_truncation, TruncateBy, Truncation};
 
 pub trait ErrorDialogUnwrapper<T, E = Error>: Sized {
     fn unwrap_or_dialog(self) -> T;
@@ -26,71 +77,211 @@ pub trait ErrorDialogUnwrapper<T, E = Error>: Sized {
 
     fn ok_unwrap_or_dialog(self) -> Option<T>;
     fn ok_unwrap_or_dialog_with_title(self, title: impl Display) -> Option<T>;
+
+    /// 指定された重要度のダイアログを表示します。プログラムは終了しません。
+    fn ok_unwrap_or_dialog_with_severity(
+        self,
+        severity: Severity,
+        title: impl Display,
+    ) -> Option<T>;
+
+    /// `parent`を親にしたダイアログを表示します。ダイアログはそのウィンドウに対してモーダルになります。
+    #[track_caller]
+    fn unwrap_or_dialog_with_parent<W: HasWindowHandle + HasDisplayHandle + ?Sized>(
+        self,
+        parent: &W,
+    ) -> T {
+        let _parent = parent::scoped_parent(parent);
+        self.unwrap_or_dialog()
+    }
+
+    /// `parent`を親にしたダイアログを表示して、`None`を返します。
+    /// `parent`が破棄される前に表示し終えるように、ダイアログが閉じられるまで待ちます。
+    #[track_caller]
+    fn ok_unwrap_or_dialog_with_parent<W: HasWindowHandle + HasDisplayHandle + ?Sized>(
+        self,
+        parent: &W,
+    ) -> Option<T> {
+        let _parent = parent::scoped_parent(parent);
+        self.ok_unwrap_or_dialog()
+    }
+
+    /// 警告のダイアログを表示して、`None`を返します。
+    #[track_caller]
+    fn ok_or_warn_dialog(self) -> Option<T> {
+        self.ok_unwrap_or_dialog_with_severity(
+            Severity::Warning,
+            get_severity_title(Severity::Warning),
+        )
+    }
+
+    /// 情報のダイアログを表示して、`None`を返します。
+    #[track_caller]
+    fn ok_or_info_dialog(self) -> Option<T> {
+        self.ok_unwrap_or_dialog_with_severity(Severity::Info, get_severity_title(Severity::Info))
+    }
+
+    /// 警告のダイアログを表示して、`T`のデフォルト値を返します。
+    /// 設定ファイルが壊れていたのでデフォルトに戻した、といった場合に使います。
+    #[track_caller]
+    fn unwrap_or_warn_dialog(self) -> T
+    where
+        T: Default,
+    {
+        self.ok_or_warn_dialog().unwrap_or_default()
+    }
+
+    /// 情報のダイアログを表示して、`T`のデフォルト値を返します。
+    #[track_caller]
+    fn unwrap_or_info_dialog(self) -> T
+    where
+        T: Default,
+    {
+        self.ok_or_info_dialog().unwrap_or_default()
+    }
 }
 
-/// エラーの説明の最大文字数。これ以上は`...`に置き換えられます。
-pub static MAX_ERROR_TEXT_LENGTH: AtomicUsize = AtomicUsize::new(512);
+fn make_request(
+    title: &str,
+    text: String,
+    severity: Severity,
+    blocking: bool,
+    location: Option<&'static Location<'static>>,
+) -> DialogRequest {
+    let text_for_dialog = get_truncation().apply(&text).into_owned();
 
-fn truncate(text: &str, index: usize) -> &str {
-    match text.char_indices().nth(index) {
-        Some((index, _)) => &text[..index],
-        None => text,
+    let mut request = DialogRequest {
+        title: title.to_owned(),
+        text,
+        description: text_for_dialog,
+        severity,
+        buttons: MessageButtons::Ok,
+        // 一時的に指定された親は呼び出しが終わると破棄されるかもしれないため、閉じられるまで待ちます。
+        blocking: blocking || parent::has_scoped_parent(),
+        location,
+        parent: get_parent_window(),
+    };
+    if let Some(location) = location.filter(|_| is_location_shown()) {
+        let location = format!("\n\n{} {}", get_message(MessageKey::Location), location);
+        request.text.push_str(&location);
+        request.description.push_str(&location);
     }
+    logging::log_request(&request);
+
+    request
 }
 
-pub fn show_error_dialog(title: &str, e: impl Debug, async_: bool) -> (&str, String) {
+/// 指定された重要度でエラーのダイアログを表示し、エラーの全文を返します。
+/// [`Throttle`]の設定によっては、ダイアログは表示されません。
+#[track_caller]
+pub fn show_dialog_with_severity<E: Debug>(
+    title: &str,
+    e: E,
+    severity: Severity,
+    blocking: bool,
+) -> String {
+    let mut request = make_request(
+        title,
+        format!("{:?}", e),
+        severity,
+        blocking,
+        Some(Location::caller()),
+    );
+    let text = request.text.clone();
+    if throttle::admit(&mut request) {
+        report::show_with_report(request);
+    }
+
+    text
+}
+
+#[track_caller]
+pub fn show_error_dialog<E: Debug>(title: &str, e: E, async_: bool) -> (&str, String) {
+    (
+        title,
+        show_dialog_with_severity(title, e, Severity::Error, !async_),
+    )
+}
+
+/// 致命的なエラーのダイアログを作ります。
+/// クラッシュレポートが設定されている場合は書き出し、そのパスを説明に書き加えます。
+#[track_caller]
+fn make_fatal_request<E: Debug>(title: &str, e: &E) -> DialogRequest {
     let text = format!("{:?}", e);
-    let text_for_dialog = format!(
-        "{}...",
-        truncate(&text, MAX_ERROR_TEXT_LENGTH.load(Ordering::SeqCst))
+    let mut request = make_request(
+        title,
+        text.clone(),
+        Severity::Error,
+        true,
+        Some(Location::caller()),
     );
+    crash_report::attach(&mut request, &text);
+    request
+}
 
-    if async_ {
-        let dialog = AsyncMessageDialog::new();
-        let _ = dialog
-            .set_title(title)
-            .set_description(&text_for_dialog)
-            .show();
-    } else {
-        let dialog = MessageDialog::new();
-        dialog
-            .set_title(title)
-            .set_description(&text_for_dialog)
-            .show();
-    };
+#[track_caller]
+fn show_fatal_dialog<E: Debug>(title: &str, e: E) -> (&str, String) {
+    let request = make_fatal_request(title, &e);
+    let text = request.text.clone();
+    report::show_with_report(request);
 
     (title, text)
 }
 
+/// [`FatalPolicy`]に従って終了します。パニックする場合は、呼び出し元の場所が報告されます。
+#[track_caller]
 fn quick_panic((title, text): (&str, String)) -> ! {
+    run_fatal_policy(title, &text);
     panic!("{}: {}", title, text)
 }
 
+/// [`quick_panic`]と同じですが、パニックのメッセージに`location`を書き加えます。
+/// 非同期の場合など、`#[track_caller]`で呼び出し元を辿れない時に使います。
+fn quick_panic_at((title, text): (&str, String), location: &Location) -> ! {
+    run_fatal_policy(title, &text);
+    panic!("{}: {}\n  at {}", title, text, location)
+}
+
+fn run_fatal_policy(title: &str, text: &str) {
+    match get_fatal_policy() {
+        FatalPolicy::Panic => {}
+        FatalPolicy::Exit(code) => process::exit(code),
+        FatalPolicy::Abort => process::abort(),
+        FatalPolicy::Callback(callback) => callback(title, text),
+    }
+
+    panic_hook::skip_dialog_for_next_panic();
+}
+
 impl<T, E: Debug> ErrorDialogUnwrapper<T, E> for Result<T, E> {
+    #[track_caller]
     fn unwrap_or_dialog(self) -> T {
         match self {
             Ok(v) => v,
-            Err(e) => quick_panic(show_error_dialog(get_title(), e, false)),
+            Err(e) => quick_panic(show_fatal_dialog(&get_title(), e)),
         }
     }
 
+    #[track_caller]
     fn unwrap_or_dialog_with_title(self, title: impl Display) -> T {
         match self {
             Ok(v) => v,
-            Err(e) => quick_panic(show_error_dialog(&format!("{}", title), e, false)),
+            Err(e) => quick_panic(show_fatal_dialog(&format!("{}", title), e)),
         }
     }
 
+    #[track_caller]
     fn ok_unwrap_or_dialog(self) -> Option<T> {
         match self {
             Ok(v) => Some(v),
             Err(e) => {
-                show_error_dialog(get_title(), e, true);
+                show_error_dialog(&get_title(), e, true);
                 None
             }
         }
     }
 
+    #[track_caller]
     fn ok_unwrap_or_dialog_with_title(self, title: impl Display) -> Option<T> {
         match self {
             Ok(v) => Some(v),
@@ -100,33 +291,63 @@ impl<T, E: Debug> ErrorDialogUnwrapper<T, E> for Result<T, E> {
             }
         }
     }
+
+    #[track_caller]
+    fn ok_unwrap_or_dialog_with_severity(
+        self,
+        severity: Severity,
+        title: impl Display,
+    ) -> Option<T> {
+        match self {
+            Ok(v) => Some(v),
+            Err(e) => {
+                show_dialog_with_severity(&format!("{}", title), e, severity, false);
+                None
+            }
+        }
+    }
 }
 
 /// 指定されたタイトルと説明でエラー時にダイアログを表示する`unwrap`をラップした関数を生成します。
+/// 説明は、引数を受け取って文字列などを返す関数で指定します。引数は`,`で区切ります。
+///
+/// ```ignore
+/// fn description(path: &str, line: usize) -> String {
+///     format!("{}の{}行目を読み込めませんでした。", path, line)
+/// }
+///
+/// define_unwrapper!("Load Error", description(path: &str, line: usize));
+/// ```
 #[macro_export]
 macro_rules! define_unwrapper {
-    ( $title:expr, $description:ident ($($arg_name:ident: $arg_type:ty)*) ) => {
-        use anyhow::{Context as _, Result};
-        use crate::misc::error::ErrorDialogUnwrapper;
-
+    ( $title:expr, $description:ident ($($arg_name:ident: $arg_type:ty),* $(,)?) ) => {
+        #[track_caller]
         pub fn unwrap_or_dialog<T>(
-            target: Result<T> $(, $arg_name: $arg_type)*
+            target: $crate::anyhow::Result<T> $(, $arg_name: $arg_type)*
         ) -> T {
-            target.context($description($($arg_name)*))
-                .unwrap_or_dialog_with_title($title)
+            $crate::ErrorDialogUnwrapper::unwrap_or_dialog_with_title(
+                $crate::anyhow::Context::context(target, $description($($arg_name),*)),
+                $title,
+            )
         }
 
+        #[track_caller]
         pub fn ok_unwrap_or_dialog<T>(
-            target: Result<T> $(, $arg_name: $arg_type)*
-        ) -> T {
-            target.context($description($($arg_name)*))
-                .unwrap_or_dialog_with_title($title)
+            target: $crate::anyhow::Result<T> $(, $arg_name: $arg_type)*
+        ) -> Option<T> {
+            $crate::ErrorDialogUnwrapper::ok_unwrap_or_dialog_with_title(
+                $crate::anyhow::Context::context(target, $description($($arg_name),*)),
+                $title,
+            )
         }
     };
 }
 
 pub mod prelude {
+    pub use super::AsyncErrorDialogUnwrapper as _;
     pub use super::ErrorDialogUnwrapper as _;
+    pub use super::OptionDialogUnwrapper as _;
+    pub use super::WithSources as _;
     pub use crate::define_unwrapper;
     pub use anyhow::{anyhow, bail, Context as _, Error, Result};
 }
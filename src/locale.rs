@@ -0,0 +1,179 @@
+use std::{
+    collections::BTreeMap,
+    env,
+    sync::{OnceLock, PoisonError, RwLock},
+};
+
+/// このクレートがダイアログに表示する組み込みの文字列の種類です。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MessageKey {
+    /// 予期せぬエラーのデフォルトのタイトルです。
+    UnexpectedError,
+    /// 警告のデフォルトのタイトルです。
+    Warning,
+    /// 情報のデフォルトのタイトルです。
+    Information,
+    /// 省略した時に末尾に付ける文字列です。
+    Ellipsis,
+    /// 省略した文字数です。`{n}`が数に置き換えられます。
+    MoreCharacters,
+    /// 省略した行数です。`{n}`が数に置き換えられます。
+    MoreLines,
+    /// エラーの原因の一覧の見出しです。
+    CausedBy,
+    /// クラッシュレポートの保存先の前に書く文章です。
+    CrashReportSaved,
+    /// クラッシュレポートを保存できなかった理由の前に書く文章です。
+    CrashReportFailed,
+    /// エラーが起きた場所の前に書く文字列です。
+    Location,
+    /// 重複して抑制したダイアログの数です。`{n}`が数に置き換えられます。
+    Suppressed,
+    Ok,
+    Yes,
+    No,
+    CopyDetails,
+    SaveReport,
+    Retry,
+    Cancel,
+    Abort,
+    Ignore,
+}
+
+fn en(key: MessageKey) -> &'static str {
+    match key {
+        MessageKey::UnexpectedError => "Unexpected Error",
+        MessageKey::Warning => "Warning",
+        MessageKey::Information => "Information",
+        MessageKey::Ellipsis => "...",
+        MessageKey::MoreCharacters => "({n} more characters)",
+        MessageKey::MoreLines => "({n} more lines)",
+        MessageKey::CausedBy => "Caused by:",
+        MessageKey::CrashReportSaved => "A crash report was saved to:",
+        MessageKey::CrashReportFailed => "The crash report could not be saved:",
+        MessageKey::Location => "Location:",
+        MessageKey::Suppressed => "This error occurred {n} more times.",
+        MessageKey::Ok => "OK",
+        MessageKey::Yes => "Yes",
+        MessageKey::No => "No",
+        MessageKey::CopyDetails => "Copy details",
+        MessageKey::SaveReport => "Save report",
+        MessageKey::Retry => "Retry",
+        MessageKey::Cancel => "Cancel",
+        MessageKey::Abort => "Abort",
+        MessageKey::Ignore => "Ignore",
+    }
+}
+
+fn ja(key: MessageKey) -> &'static str {
+    match key {
+        MessageKey::UnexpectedError => "予期せぬエラー",
+        MessageKey::Warning => "警告",
+        MessageKey::Information => "情報",
+        MessageKey::Ellipsis => "…",
+        MessageKey::MoreCharacters => "（他{n}文字）",
+        MessageKey::MoreLines => "（他{n}行）",
+        MessageKey::CausedBy => "原因:",
+        MessageKey::CrashReportSaved => "クラッシュレポートを保存しました:",
+        MessageKey::CrashReportFailed => "クラッシュレポートを保存できませんでした:",
+        MessageKey::Location => "発生場所:",
+        MessageKey::Suppressed => "このエラーは他に{n}回発生しました。",
+        MessageKey::Ok => "OK",
+        MessageKey::Yes => "はい",
+        MessageKey::No => "いいえ",
+        MessageKey::CopyDetails => "詳細をコピー",
+        MessageKey::SaveReport => "レポートを保存",
+        MessageKey::Retry => "再試行",
+        MessageKey::Cancel => "キャンセル",
+        MessageKey::Abort => "中止",
+        MessageKey::Ignore => "無視",
+    }
+}
+
+fn builtin(locale: &str, key: MessageKey) -> Option<&'static str> {
+    match locale {
+        "en" => Some(en(key)),
+        "ja" => Some(ja(key)),
+        _ => None,
+    }
+}
+
+static LOCALE: RwLock<Option<String>> = RwLock::new(None);
+static CATALOG: RwLock<BTreeMap<String, BTreeMap<MessageKey, String>>> =
+    RwLock::new(BTreeMap::new());
+
+/// `ja_JP.UTF-8`のようなロケールを`ja-JP`のような形にします。
+fn normalize(locale: &str) -> String {
+    locale
+        .split(['.', '@'])
+        .next()
+        .unwrap_or_default()
+        .replace('_', "-")
+}
+
+/// 環境変数の`LC_ALL`、`LC_MESSAGES`、`LANG`の順に見て、ロケールを検出します。
+fn detect() -> &'static str {
+    static DETECTED: OnceLock<String> = OnceLock::new();
+
+    DETECTED.get_or_init(|| {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .map(|value| normalize(&value))
+            .filter(|locale| !locale.is_empty() && locale != "C" && locale != "POSIX")
+            .unwrap_or_else(|| String::from("en"))
+    })
+}
+
+/// ダイアログに表示する文字列のロケールを設定します。
+/// 設定されていない場合は、環境変数から検出されます。
+pub fn set_locale(locale: impl AsRef<str>) {
+    *LOCALE.write().unwrap_or_else(PoisonError::into_inner) = Some(normalize(locale.as_ref()));
+}
+
+/// 現在のロケールを取得します。
+pub fn get_locale() -> String {
+    LOCALE
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+        .unwrap_or_else(|| detect().to_owned())
+}
+
+/// 言語を追加します。既に登録されている文字列は上書きされます。
+/// 登録されていない文字列は、言語部分が同じロケール、英語の順に探されます。
+pub fn register_locale<S: Into<String>>(
+    locale: impl AsRef<str>,
+    messages: impl IntoIterator<Item = (MessageKey, S)>,
+) {
+    CATALOG
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entry(normalize(locale.as_ref()))
+        .or_default()
+        .extend(messages.into_iter().map(|(key, text)| (key, text.into())));
+}
+
+/// 指定されたロケールの文字列を一つだけ上書きします。
+pub fn set_message(locale: impl AsRef<str>, key: MessageKey, text: impl Into<String>) {
+    register_locale(locale, [(key, text)]);
+}
+
+/// 現在のロケールでの文字列を取得します。
+pub fn get_message(key: MessageKey) -> String {
+    let locale = get_locale();
+    let language = locale.split('-').next().unwrap_or_default();
+    let catalog = CATALOG.read().unwrap_or_else(PoisonError::into_inner);
+
+    let message = [locale.as_str(), language, "en"]
+        .into_iter()
+        .find_map(|locale| {
+            catalog
+                .get(locale)
+                .and_then(|messages| messages.get(&key).cloned())
+                .or_else(|| builtin(locale, key).map(str::to_owned))
+        });
+
+    message.unwrap_or_else(|| en(key).to_owned())
+}
@@ -0,0 +1,60 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rfd::MessageDialogResult;
+
+use crate::{crash_report::utc, terminal::choices, DialogBackend, DialogRequest};
+
+/// ダイアログの内容をファイルに追記するバックエンドです。
+/// 他の方法ではダイアログを表示できなかった場合の、最後の手段として使います。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LogFileBackend {
+    path: PathBuf,
+}
+
+impl LogFileBackend {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl DialogBackend for LogFileBackend {
+    fn show(&self, request: &DialogRequest) -> MessageDialogResult {
+        self.try_show(request).unwrap_or_default()
+    }
+
+    fn try_show(&self, request: &DialogRequest) -> io::Result<MessageDialogResult> {
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let (year, month, day, hour, minute, second) = utc(now.as_secs());
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(
+            file,
+            "[{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC] {:?}: {}\n{}\n",
+            year, month, day, hour, minute, second, request.severity, request.title, request.text
+        )?;
+
+        // ファイルではボタンを押せないため、ダイアログを閉じた時の扱いにします。
+        Ok(choices(&request.buttons)
+            .pop()
+            .map(|(_, result)| result)
+            .unwrap_or_default())
+    }
+
+    fn name(&self) -> &str {
+        "log-file"
+    }
+}
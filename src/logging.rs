@@ -0,0 +1,140 @@
+use std::{
+    io,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use rfd::MessageDialogResult;
+
+use crate::DialogRequest;
+
+static LOGGING: AtomicBool = AtomicBool::new(true);
+
+/// ダイアログを表示する前に、その内容をログに出力するかどうかを設定します。
+/// `log`か`tracing`のフィーチャーが有効な場合のみ出力され、デフォルトでは出力します。
+pub fn set_logging(enabled: bool) {
+    LOGGING.store(enabled, Ordering::SeqCst);
+}
+
+/// ダイアログの内容をログに出力するかどうかを取得します。
+pub fn is_logging_enabled() -> bool {
+    LOGGING.load(Ordering::SeqCst)
+}
+
+#[cfg(feature = "log")]
+fn log(request: &DialogRequest) {
+    use crate::Severity;
+
+    let level = match request.severity {
+        Severity::Error => log::Level::Error,
+        Severity::Warning => log::Level::Warn,
+        Severity::Info => log::Level::Info,
+    };
+    match request.location {
+        Some(location) => log::log!(
+            target: "dialog_unwrapper",
+            level,
+            "{}: {} (at {})",
+            request.title,
+            request.text,
+            location
+        ),
+        None => log::log!(target: "dialog_unwrapper", level, "{}: {}", request.title, request.text),
+    }
+}
+
+#[cfg(feature = "tracing")]
+fn trace(request: &DialogRequest) {
+    use crate::Severity;
+
+    let (title, text) = (&request.title, &request.text);
+    let location = request.location.map(tracing::field::display);
+    match request.severity {
+        Severity::Error => {
+            tracing::error!(target: "dialog_unwrapper", %title, location, "{}", text)
+        }
+        Severity::Warning => {
+            tracing::warn!(target: "dialog_unwrapper", %title, location, "{}", text)
+        }
+        Severity::Info => tracing::info!(target: "dialog_unwrapper", %title, location, "{}", text),
+    }
+}
+
+/// 今いる`tracing`のスパンを、ダイアログの詳細に書き加えます。
+/// 違うスパンで起きた同じエラーが重複の抑制で区別されないように、表示すると決まった後に呼び出します。
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn attach_span(request: &mut DialogRequest) {
+    #[cfg(feature = "tracing")]
+    if let Some(metadata) = tracing::Span::current().metadata() {
+        let span = format!("\n\nSpan: {}::{}", metadata.target(), metadata.name());
+        request.text.push_str(&span);
+        request.description.push_str(&span);
+    }
+}
+
+/// ダイアログを表示する前に、省略されていないエラーの全文をログに出力します。
+#[cfg_attr(
+    not(any(feature = "log", feature = "tracing")),
+    allow(unused_variables)
+)]
+pub(crate) fn log_request(request: &DialogRequest) {
+    if is_logging_enabled() {
+        #[cfg(feature = "log")]
+        log(request);
+        #[cfg(feature = "tracing")]
+        trace(request);
+    }
+}
+
+/// [`FallbackBackend`](crate::FallbackBackend)で、どのバックエンドで表示できたか、または失敗したかをログに出力します。
+#[cfg_attr(
+    not(any(feature = "log", feature = "tracing")),
+    allow(unused_variables)
+)]
+pub(crate) fn log_backend(name: &str, result: &io::Result<MessageDialogResult>) {
+    if !is_logging_enabled() {
+        return;
+    }
+
+    match result {
+        Ok(_) => {
+            #[cfg(feature = "log")]
+            log::debug!(target: "dialog_unwrapper", "dialog shown with {}", name);
+            #[cfg(feature = "tracing")]
+            tracing::debug!(target: "dialog_unwrapper", backend = name, "dialog shown");
+        }
+        Err(e) => {
+            #[cfg(feature = "log")]
+            log::warn!(target: "dialog_unwrapper", "{} failed to show the dialog: {}", name, e);
+            #[cfg(feature = "tracing")]
+            tracing::warn!(target: "dialog_unwrapper", backend = name, error = %e, "failed to show the dialog");
+        }
+    }
+}
+
+/// 重複の抑制で、表示されないまま忘れられたダイアログの回数をログに出力します。
+#[cfg_attr(
+    not(any(feature = "log", feature = "tracing")),
+    allow(unused_variables)
+)]
+pub(crate) fn log_suppressed(key: &str, count: usize) {
+    if is_logging_enabled() {
+        #[cfg(feature = "log")]
+        log::warn!(target: "dialog_unwrapper", "{} dialogs were suppressed: {}", count, key);
+        #[cfg(feature = "tracing")]
+        tracing::warn!(target: "dialog_unwrapper", key, count, "dialogs were suppressed");
+    }
+}
+
+/// 致命的なエラーのクラッシュレポートを書き出せなかったことをログに出力します。
+#[cfg_attr(
+    not(any(feature = "log", feature = "tracing")),
+    allow(unused_variables)
+)]
+pub(crate) fn log_crash_report_failure(error: &io::Error) {
+    if is_logging_enabled() {
+        #[cfg(feature = "log")]
+        log::error!(target: "dialog_unwrapper", "failed to write the crash report: {}", error);
+        #[cfg(feature = "tracing")]
+        tracing::error!(target: "dialog_unwrapper", %error, "failed to write the crash report");
+    }
+}
@@ -0,0 +1,107 @@
+use std::{
+    fmt::{self, Debug},
+    sync::{mpsc::channel, Arc, PoisonError, RwLock},
+    thread::{self, ThreadId},
+};
+
+/// メインスレッドで実行してほしい、ダイアログを表示する処理です。
+pub type DialogTask = Box<dyn FnOnce() + Send>;
+
+/// ダイアログをアプリのメインスレッドで表示するためのフックです。
+/// winitやtao、eguiなどのイベントループを使うアプリで、ダイアログをメインスレッドから表示しなければならない場合に使います。
+///
+/// ```ignore
+/// let proxy = event_loop.create_proxy();
+/// dialog_unwrapper::set_main_thread_dispatcher(Some(MainThreadDispatcher::new(move |task| {
+///     let _ = proxy.send_event(UserEvent::ShowDialog(task));
+/// })));
+///
+/// // イベントループで
+/// Event::UserEvent(UserEvent::ShowDialog(task)) => task(),
+/// ```
+#[derive(Clone)]
+pub struct MainThreadDispatcher {
+    dispatch: Arc<dyn Fn(DialogTask) + Send + Sync>,
+    thread: ThreadId,
+}
+
+impl MainThreadDispatcher {
+    /// メインスレッドから呼び出してください。
+    /// `dispatch`は受け取った[`DialogTask`]をイベントループに送り、メインスレッドで実行されるようにします。
+    /// タスクが実行されずに捨てられた場合は、ダイアログは閉じられたものとして扱われます。
+    pub fn new(dispatch: impl Fn(DialogTask) + Send + Sync + 'static) -> Self {
+        Self {
+            dispatch: Arc::new(dispatch),
+            thread: thread::current().id(),
+        }
+    }
+
+    /// 今のスレッドがメインスレッドかどうかを取得します。
+    pub fn is_main_thread(&self) -> bool {
+        thread::current().id() == self.thread
+    }
+
+    /// `f`をメインスレッドで実行し、終わるまで待ちます。
+    fn run<R: Send + 'static>(&self, f: impl FnOnce() -> R + Send + 'static) -> Option<R> {
+        let (sender, receiver) = channel();
+        (self.dispatch)(Box::new(move || {
+            let _ = sender.send(f());
+        }));
+        receiver.recv().ok()
+    }
+}
+
+impl Debug for MainThreadDispatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MainThreadDispatcher")
+            .field("thread", &self.thread)
+            .finish_non_exhaustive()
+    }
+}
+
+static MAIN_THREAD_DISPATCHER: RwLock<Option<MainThreadDispatcher>> = RwLock::new(None);
+
+/// ダイアログをメインスレッドで表示するためのフックを設定します。
+/// 設定されていない場合は、`rfd`のダイアログは専用のスレッドから表示されます。
+pub fn set_main_thread_dispatcher(dispatcher: Option<MainThreadDispatcher>) {
+    *MAIN_THREAD_DISPATCHER
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = dispatcher;
+}
+
+/// 現在のフックを取得します。
+pub fn get_main_thread_dispatcher() -> Option<MainThreadDispatcher> {
+    MAIN_THREAD_DISPATCHER
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// 今のスレッドが、macOSのプロセスのメインスレッドかどうかを取得します。
+/// `rfd`がメインスレッドで表示しようとするのはmacOSのみのため、他の環境では常に`false`を返します。
+#[cfg(target_os = "macos")]
+pub(crate) fn is_macos_main_thread() -> bool {
+    extern "C" {
+        fn pthread_main_np() -> std::ffi::c_int;
+    }
+
+    // SAFETY: 引数を取らず、今のスレッドがメインスレッドかどうかを返すだけの関数です。
+    unsafe { pthread_main_np() != 0 }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub(crate) fn is_macos_main_thread() -> bool {
+    false
+}
+
+/// フックが設定されている場合は`f`をメインスレッドで実行し、終わるまで待ちます。
+/// 設定されていない場合や、既にメインスレッドにいる場合はこの場で実行します。
+/// タスクが捨てられた場合は`None`を返します。
+pub(crate) fn on_main_thread<R: Send + 'static>(
+    f: impl FnOnce() -> R + Send + 'static,
+) -> Option<R> {
+    match get_main_thread_dispatcher() {
+        Some(dispatcher) if !dispatcher.is_main_thread() => dispatcher.run(f),
+        _ => Some(f()),
+    }
+}
@@ -0,0 +1,48 @@
+use std::fmt::Display;
+
+use anyhow::Error;
+
+use crate::ErrorDialogUnwrapper;
+
+/// [`ErrorDialogUnwrapper`]の`Option`版です。`None`だった場合は、渡されたメッセージをダイアログで表示します。
+pub trait OptionDialogUnwrapper<T>: Sized {
+    fn unwrap_or_dialog(self, message: impl Display) -> T;
+    fn unwrap_or_dialog_with_title(self, title: impl Display, message: impl Display) -> T;
+
+    fn ok_unwrap_or_dialog(self, message: impl Display) -> Option<T>;
+    fn ok_unwrap_or_dialog_with_title(
+        self,
+        title: impl Display,
+        message: impl Display,
+    ) -> Option<T>;
+}
+
+fn into_result<T>(option: Option<T>, message: impl Display) -> Result<T, Error> {
+    option.ok_or_else(|| Error::msg(message.to_string()))
+}
+
+impl<T> OptionDialogUnwrapper<T> for Option<T> {
+    #[track_caller]
+    fn unwrap_or_dialog(self, message: impl Display) -> T {
+        into_result(self, message).unwrap_or_dialog()
+    }
+
+    #[track_caller]
+    fn unwrap_or_dialog_with_title(self, title: impl Display, message: impl Display) -> T {
+        into_result(self, message).unwrap_or_dialog_with_title(title)
+    }
+
+    #[track_caller]
+    fn ok_unwrap_or_dialog(self, message: impl Display) -> Option<T> {
+        into_result(self, message).ok_unwrap_or_dialog()
+    }
+
+    #[track_caller]
+    fn ok_unwrap_or_dialog_with_title(
+        self,
+        title: impl Display,
+        message: impl Display,
+    ) -> Option<T> {
+        into_result(self, message).ok_unwrap_or_dialog_with_title(title)
+    }
+}
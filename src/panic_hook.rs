@@ -0,0 +1,65 @@
+use std::{
+    backtrace::{Backtrace, BacktraceStatus},
+    cell::Cell,
+    panic::{self, PanicHookInfo},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::{crash_report, dispatcher, get_title, make_request, report, Severity};
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// このスレッドで次に起きるパニックで、ダイアログを表示しないかどうかです。
+    static SKIP_DIALOG: Cell<bool> = const { Cell::new(false) };
+}
+
+/// 既にダイアログを表示した後のパニックで、もう一度ダイアログが表示されないようにします。
+pub(crate) fn skip_dialog_for_next_panic() {
+    if INSTALLED.load(Ordering::SeqCst) {
+        SKIP_DIALOG.set(true);
+    }
+}
+
+fn panic_text(info: &PanicHookInfo) -> String {
+    let payload = info.payload();
+    let message = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("Box<dyn Any>");
+
+    let mut text = String::from(message);
+    if let Some(location) = info.location() {
+        text.push_str(&format!("\n\nat {}", location));
+    }
+
+    let backtrace = Backtrace::capture();
+    if backtrace.status() == BacktraceStatus::Captured {
+        text.push_str(&format!("\n\n{}", backtrace));
+    }
+
+    text
+}
+
+/// パニックが起きた時に、その内容をダイアログで表示するパニックフックを設定します。
+/// 以前に設定されていたフックも、ダイアログが閉じられた後に呼び出されます。
+/// バックトレースは`RUST_BACKTRACE`が設定されている場合のみ表示されます。
+pub fn install_panic_hook() {
+    if INSTALLED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        // ダイアログを表示する専用のスレッドでのパニックは、ダイアログの表示に失敗したものなので、もう一度表示しようとしません。
+        if !SKIP_DIALOG.replace(false) && !dispatcher::is_dispatcher_thread() {
+            let text = panic_text(info);
+            let mut request = make_request(&get_title(), text.clone(), Severity::Error, true, None);
+            crash_report::attach(&mut request, &text);
+            report::show_with_report(request);
+        }
+
+        previous(info);
+    }));
+}
@@ -0,0 +1,123 @@
+use std::{
+    cell::RefCell,
+    sync::{PoisonError, RwLock},
+};
+
+use raw_window_handle::{
+    DisplayHandle, HandleError, HasDisplayHandle, HasWindowHandle, RawDisplayHandle,
+    RawWindowHandle, WindowHandle,
+};
+
+/// ダイアログの親にするウィンドウです。親があるダイアログは、そのウィンドウに対してモーダルになります。
+/// ハンドルをそのまま保持するため、作るには[`ParentWindow::new`]の安全性の条件を満たす必要があります。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParentWindow {
+    window: RawWindowHandle,
+    display: RawDisplayHandle,
+}
+
+// ハンドルはダイアログに渡すまで保持するだけで、このクレートが中身を読み書きすることはありません。
+// 別のスレッドで使われてもウィンドウが生きていることは、`ParentWindow::new`の呼び出し元が保証します。
+unsafe impl Send for ParentWindow {}
+unsafe impl Sync for ParentWindow {}
+
+impl ParentWindow {
+    /// winitなどのウィンドウからハンドルを取得します。
+    ///
+    /// # Safety
+    ///
+    /// 戻り値やそのコピーを親にしたダイアログが全て閉じられるまで、`window`を閉じたり破棄したりしないでください。
+    /// ダイアログは別のスレッドやメインスレッドで表示されることがあるため、そのスレッドからもハンドルを使える必要があります。
+    pub unsafe fn new<W: HasWindowHandle + HasDisplayHandle + ?Sized>(
+        window: &W,
+    ) -> Result<Self, HandleError> {
+        Ok(Self {
+            window: window.window_handle()?.as_raw(),
+            display: window.display_handle()?.as_raw(),
+        })
+    }
+}
+
+impl HasWindowHandle for ParentWindow {
+    fn window_handle(&self) -> Result<WindowHandle<'_>, HandleError> {
+        // SAFETY: ウィンドウが生きている間だけ使われることを、利用者に求めています。
+        Ok(unsafe { WindowHandle::borrow_raw(self.window) })
+    }
+}
+
+impl HasDisplayHandle for ParentWindow {
+    fn display_handle(&self) -> Result<DisplayHandle<'_>, HandleError> {
+        // SAFETY: 同上です。
+        Ok(unsafe { DisplayHandle::borrow_raw(self.display) })
+    }
+}
+
+static PARENT_WINDOW: RwLock<Option<ParentWindow>> = RwLock::new(None);
+
+thread_local! {
+    /// `unwrap_or_dialog_with_parent`などで一時的に指定された、このスレッドでの親です。
+    static SCOPED_PARENTS: RefCell<Vec<ParentWindow>> = const { RefCell::new(Vec::new()) };
+}
+
+/// 全てのダイアログのデフォルトの親にするウィンドウを設定します。
+///
+/// # Safety
+///
+/// [`clear_parent_window`]を呼び出し、それまでに表示したダイアログが全て閉じられるまで、`window`を閉じたり破棄したりしないでください。
+/// その他の条件は[`ParentWindow::new`]と同じです。
+pub unsafe fn set_parent_window<W: HasWindowHandle + HasDisplayHandle + ?Sized>(
+    window: &W,
+) -> Result<(), HandleError> {
+    install_parent_window(unsafe { ParentWindow::new(window)? });
+    Ok(())
+}
+
+pub(crate) fn install_parent_window(parent: ParentWindow) {
+    *PARENT_WINDOW
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(parent);
+}
+
+/// デフォルトの親を解除します。以降のダイアログはどのウィンドウにも属さずに表示されます。
+pub fn clear_parent_window() {
+    *PARENT_WINDOW
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+/// 次に表示するダイアログの親を取得します。一時的に指定されたものが優先されます。
+pub fn get_parent_window() -> Option<ParentWindow> {
+    SCOPED_PARENTS
+        .with_borrow(|parents| parents.last().copied())
+        .or_else(|| *PARENT_WINDOW.read().unwrap_or_else(PoisonError::into_inner))
+}
+
+/// このスレッドで一時的に親が指定されているかどうかを取得します。
+/// 指定されている間のダイアログは、親が閉じられる前に表示し終えるように、閉じられるまで待ちます。
+pub(crate) fn has_scoped_parent() -> bool {
+    SCOPED_PARENTS.with_borrow(|parents| !parents.is_empty())
+}
+
+/// 戻り値がドロップされるまで、このスレッドから表示されるダイアログの親を`window`にします。
+/// ハンドルを取得できなかった場合は、今の親のままになります。
+pub(crate) fn scoped_parent<'a, W: HasWindowHandle + HasDisplayHandle + ?Sized>(
+    window: &'a W,
+) -> impl Drop + 'a {
+    struct Guard(bool);
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            if self.0 {
+                SCOPED_PARENTS.with_borrow_mut(|parents| parents.pop());
+            }
+        }
+    }
+
+    // SAFETY: `window`は戻り値が生きている間は借用されたままで、その間のダイアログは閉じられるまで待つため、
+    // ダイアログを表示し終えるまでウィンドウは破棄されません。
+    let parent = unsafe { ParentWindow::new(window) }.ok();
+    if let Some(parent) = parent {
+        SCOPED_PARENTS.with_borrow_mut(|parents| parents.push(parent));
+    }
+    Guard(parent.is_some())
+}
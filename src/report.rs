@@ -0,0 +1,174 @@
+use std::{
+    fmt::Display,
+    fs,
+    future::Future,
+    sync::{PoisonError, RwLock},
+};
+
+#[cfg(feature = "clipboard")]
+use std::sync::Mutex;
+
+use rfd::{AsyncFileDialog, FileDialog, MessageButtons, MessageDialogResult};
+
+use crate::{get_backend, get_message, logging, main_thread, DialogRequest, MessageKey};
+
+const REPORT_FILE_NAME: &str = "error-report.txt";
+
+/// ダイアログが閉じられるのを待つ時に、追加で表示するボタンです。
+/// ボタンが押された場合は、その処理をした後にもう一度ダイアログが表示されます。
+///
+/// Windowsでは、`rfd`の`common-controls-v6`フィーチャーが有効でないと正しく表示されません。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ReportButtons {
+    /// エラーの全文をクリップボードにコピーするボタンです。`clipboard`フィーチャーが必要です。
+    pub copy: bool,
+    /// エラーの全文を、ユーザーが選んだファイルに保存するボタンです。
+    pub save: bool,
+}
+
+/// 押されたボタンを判別できるように、ダイアログを表示する前に取得しておくボタンの文字列です。
+struct Labels {
+    copy: String,
+    save: String,
+}
+
+impl Labels {
+    fn new() -> Self {
+        Self {
+            copy: get_message(MessageKey::CopyDetails),
+            save: get_message(MessageKey::SaveReport),
+        }
+    }
+}
+
+impl ReportButtons {
+    fn message_buttons(self, labels: &Labels) -> MessageButtons {
+        let copy = self.copy && cfg!(feature = "clipboard");
+        let ok = get_message(MessageKey::Ok);
+
+        match (copy, self.save) {
+            (true, true) => {
+                MessageButtons::YesNoCancelCustom(labels.copy.clone(), labels.save.clone(), ok)
+            }
+            (true, false) => MessageButtons::OkCancelCustom(labels.copy.clone(), ok),
+            (false, true) => MessageButtons::OkCancelCustom(labels.save.clone(), ok),
+            (false, false) => MessageButtons::Ok,
+        }
+    }
+}
+
+static REPORT_BUTTONS: RwLock<ReportButtons> = RwLock::new(ReportButtons {
+    copy: false,
+    save: false,
+});
+
+/// ダイアログに追加で表示するボタンを設定します。デフォルトでは何も追加されません。
+pub fn set_report_buttons(buttons: ReportButtons) {
+    *REPORT_BUTTONS
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = buttons;
+}
+
+/// 現在の追加で表示するボタンを取得します。
+pub fn get_report_buttons() -> ReportButtons {
+    *REPORT_BUTTONS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+/// X11やWaylandでは、コピーした`Clipboard`が捨てられると内容も失われるため、一度作ったものを使い続けます。
+#[cfg(feature = "clipboard")]
+static CLIPBOARD: Mutex<Option<arboard::Clipboard>> = Mutex::new(None);
+
+#[cfg(feature = "clipboard")]
+fn copy(text: &str) -> Result<(), arboard::Error> {
+    let mut slot = CLIPBOARD.lock().unwrap_or_else(PoisonError::into_inner);
+    let clipboard = match slot.take() {
+        Some(clipboard) => clipboard,
+        None => arboard::Clipboard::new()?,
+    };
+    slot.insert(clipboard).set_text(text)
+}
+
+/// ボタンの処理に失敗したことを、元のダイアログと同じタイトルで伝えるダイアログを作ります。
+fn failure(request: &DialogRequest, e: impl Display) -> DialogRequest {
+    let text = e.to_string();
+    DialogRequest {
+        text: text.clone(),
+        description: text,
+        buttons: MessageButtons::Ok,
+        ..request.clone()
+    }
+}
+
+/// ダイアログを表示します。閉じられるのを待つ場合は、追加のボタンとその処理も行います。
+pub(crate) fn show_with_report(mut request: DialogRequest) -> MessageDialogResult {
+    logging::attach_span(&mut request);
+    let backend = get_backend();
+    if !request.blocking {
+        return backend.show(&request);
+    }
+
+    let labels = Labels::new();
+    request.buttons = get_report_buttons().message_buttons(&labels);
+    loop {
+        match backend.show(&request) {
+            #[cfg(feature = "clipboard")]
+            MessageDialogResult::Custom(label) if label == labels.copy => {
+                if let Err(e) = copy(&request.text) {
+                    backend.show(&failure(&request, e));
+                }
+            }
+            MessageDialogResult::Custom(label) if label == labels.save => {
+                let parent = request.parent;
+                let path = main_thread::on_main_thread(move || {
+                    let mut dialog = FileDialog::new().set_file_name(REPORT_FILE_NAME);
+                    if let Some(parent) = &parent {
+                        dialog = dialog.set_parent(parent);
+                    }
+                    dialog.save_file()
+                })
+                .flatten();
+                if let Some(Err(e)) = path.map(|path| fs::write(path, &request.text)) {
+                    backend.show(&failure(&request, e));
+                }
+            }
+            result => return result,
+        }
+    }
+}
+
+/// [`show_with_report`]の非同期版です。
+/// バックエンドは呼び出したスレッドで取得するため、Futureが別のスレッドで実行されても同じものが使われます。
+pub(crate) fn show_with_report_async(
+    mut request: DialogRequest,
+) -> impl Future<Output = MessageDialogResult> + Send + 'static {
+    logging::attach_span(&mut request);
+    let backend = get_backend();
+    let labels = Labels::new();
+    request.buttons = get_report_buttons().message_buttons(&labels);
+
+    async move {
+        loop {
+            match backend.show_async(request.clone()).await {
+                #[cfg(feature = "clipboard")]
+                MessageDialogResult::Custom(label) if label == labels.copy => {
+                    if let Err(e) = copy(&request.text) {
+                        backend.show_async(failure(&request, e)).await;
+                    }
+                }
+                MessageDialogResult::Custom(label) if label == labels.save => {
+                    let mut dialog = AsyncFileDialog::new().set_file_name(REPORT_FILE_NAME);
+                    if let Some(parent) = &request.parent {
+                        dialog = dialog.set_parent(parent);
+                    }
+                    let file = dialog.save_file().await;
+                    if let Some(Err(e)) = file.map(|file| fs::write(file.path(), &request.text)) {
+                        backend.show_async(failure(&request, e)).await;
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+}
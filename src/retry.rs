@@ -0,0 +1,233 @@
+use std::{fmt::Debug, panic::Location};
+
+use rfd::{MessageButtons, MessageDialogResult};
+
+use crate::{
+    get_backend, get_crash_report, get_message, get_title, logging, make_fatal_request,
+    make_request, quick_panic, MessageKey, Severity,
+};
+
+/// 失敗した時にダイアログで再試行するかどうかをユーザーに尋ねる処理の設定です。
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Retry {
+    /// ダイアログのタイトルです。`None`の場合は[`get_title`]が使われます。
+    pub title: Option<String>,
+    /// 処理を実行する最大の回数です。`None`の場合は何度でも再試行できます。
+    /// 最後の試行で失敗した場合は、再試行のボタンが表示されません。
+    pub max_attempts: Option<usize>,
+}
+
+impl Retry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    fn title_or_default(&self) -> String {
+        self.title.clone().unwrap_or_else(get_title)
+    }
+
+    /// エラーを表示して、押されたボタンを返します。
+    /// ボタンは`buttons`に再試行できるかどうかを渡して決め、最後のものがダイアログを閉じた時の扱いになります。
+    #[track_caller]
+    fn ask<E: Debug>(
+        &self,
+        e: &E,
+        attempt: usize,
+        buttons: impl FnOnce(bool) -> Vec<MessageKey>,
+    ) -> Option<MessageKey> {
+        let can_retry = self.max_attempts.is_none_or(|max| attempt < max);
+        let labels: Vec<_> = buttons(can_retry)
+            .into_iter()
+            .map(|key| (key, get_message(key)))
+            .collect();
+
+        let mut request = make_request(
+            &self.title_or_default(),
+            format!("{:?}", e),
+            Severity::Error,
+            true,
+            Some(Location::caller()),
+        );
+        request.buttons = match &labels[..] {
+            [(_, a), (_, b)] => MessageButtons::OkCancelCustom(a.clone(), b.clone()),
+            [(_, a), (_, b), (_, c)] => {
+                MessageButtons::YesNoCancelCustom(a.clone(), b.clone(), c.clone())
+            }
+            _ => MessageButtons::Ok,
+        };
+
+        logging::attach_span(&mut request);
+        match get_backend().show(&request) {
+            MessageDialogResult::Custom(pressed) => labels
+                .into_iter()
+                .find_map(|(key, label)| (label == pressed).then_some(key)),
+            _ => None,
+        }
+    }
+
+    /// 処理を実行し、失敗した場合は「再試行」と「キャンセル」のボタンがあるダイアログを表示します。
+    /// キャンセルされた場合は、最後のエラーを返します。
+    #[track_caller]
+    pub fn run<T, E: Debug>(&self, mut op: impl FnMut() -> Result<T, E>) -> Result<T, E> {
+        let mut attempt = 1;
+        loop {
+            let e = match op() {
+                Ok(v) => return Ok(v),
+                Err(e) => e,
+            };
+
+            let pressed = self.ask(&e, attempt, |can_retry| {
+                if can_retry {
+                    vec![MessageKey::Retry, MessageKey::Cancel]
+                } else {
+                    vec![MessageKey::Ok]
+                }
+            });
+            match pressed {
+                Some(MessageKey::Retry) => attempt += 1,
+                _ => return Err(e),
+            }
+        }
+    }
+
+    /// 処理を実行し、失敗した場合は「中止」と「再試行」、「無視」のボタンがあるダイアログを表示します。
+    /// 無視された場合は`None`を返し、中止された場合は[`FatalPolicy`](crate::FatalPolicy)に従って終了します。
+    #[track_caller]
+    pub fn run_or_ignore<T, E: Debug>(&self, mut op: impl FnMut() -> Result<T, E>) -> Option<T> {
+        let mut attempt = 1;
+        loop {
+            let e = match op() {
+                Ok(v) => return Some(v),
+                Err(e) => e,
+            };
+
+            let pressed = self.ask(&e, attempt, |can_retry| {
+                if can_retry {
+                    vec![MessageKey::Abort, MessageKey::Retry, MessageKey::Ignore]
+                } else {
+                    vec![MessageKey::Abort, MessageKey::Ignore]
+                }
+            });
+
+            match pressed {
+                Some(MessageKey::Retry) => attempt += 1,
+                Some(MessageKey::Abort) => {
+                    let title = self.title_or_default();
+                    let mut request = make_fatal_request(&title, &e);
+                    // クラッシュレポートを書き出した場合は、その保存先を伝えます。
+                    if get_crash_report().is_some() {
+                        logging::attach_span(&mut request);
+                        get_backend().show(&request);
+                    }
+                    quick_panic((&title, request.text))
+                }
+                _ => return None,
+            }
+        }
+    }
+}
+
+/// 処理を実行し、失敗した場合は再試行するかどうかをダイアログで尋ねます。
+/// キャンセルされた場合は、最後のエラーを返します。
+#[track_caller]
+pub fn retry_or_dialog<T, E: Debug>(op: impl FnMut() -> Result<T, E>) -> Result<T, E> {
+    Retry::new().run(op)
+}
+
+/// 処理を実行し、失敗した場合は中止か再試行、無視のどれにするかをダイアログで尋ねます。
+/// 無視された場合は`None`を返します。
+#[track_caller]
+pub fn retry_or_ignore_dialog<T, E: Debug>(op: impl FnMut() -> Result<T, E>) -> Option<T> {
+    Retry::new().run_or_ignore(op)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::RecordingBackend;
+
+    fn press(recorder: &RecordingBackend, key: MessageKey) {
+        recorder.push_response(MessageDialogResult::Custom(get_message(key)));
+    }
+
+    #[test]
+    fn retry_then_success() {
+        let recorder = RecordingBackend::install();
+        press(&recorder, MessageKey::Retry);
+
+        let mut attempts = 0;
+        let result = Retry::new().run(|| {
+            attempts += 1;
+            if attempts == 1 {
+                Err("busy")
+            } else {
+                Ok(attempts)
+            }
+        });
+
+        assert_eq!(result, Ok(2));
+        recorder.assert_count(1);
+    }
+
+    #[test]
+    fn cancel_returns_the_error() {
+        let recorder = RecordingBackend::install();
+        press(&recorder, MessageKey::Cancel);
+
+        let result = Retry::new().title("Sync").run(|| Err::<(), _>("offline"));
+
+        assert_eq!(result, Err("offline"));
+        recorder.assert_last("Sync", "offline");
+    }
+
+    #[test]
+    fn max_attempts_removes_the_retry_button() {
+        let recorder = RecordingBackend::install();
+        press(&recorder, MessageKey::Retry);
+
+        let mut attempts = 0;
+        let result = Retry::new().max_attempts(2).run(|| {
+            attempts += 1;
+            Err::<(), _>(attempts)
+        });
+
+        assert_eq!(result, Err(2));
+        let records = recorder.take();
+        assert_eq!(records.len(), 2);
+        assert!(matches!(
+            &records[0].buttons,
+            MessageButtons::OkCancelCustom(retry, _) if *retry == get_message(MessageKey::Retry)
+        ));
+        assert!(matches!(records[1].buttons, MessageButtons::Ok));
+
+        Retry::new()
+            .max_attempts(1)
+            .run_or_ignore(|| Err::<(), _>("failed"));
+        assert!(matches!(
+            recorder.last().unwrap().buttons,
+            MessageButtons::OkCancelCustom(abort, ignore)
+                if abort == get_message(MessageKey::Abort) && ignore == get_message(MessageKey::Ignore)
+        ));
+    }
+
+    #[test]
+    fn ignore_returns_none() {
+        let recorder = RecordingBackend::install();
+        press(&recorder, MessageKey::Ignore);
+
+        let result = Retry::new().run_or_ignore(|| Err::<(), _>("failed"));
+
+        assert_eq!(result, None);
+        recorder.assert_count(1);
+    }
+}
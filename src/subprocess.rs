@@ -0,0 +1,175 @@
+use std::{
+    env, io,
+    process::{Command, Stdio},
+    thread,
+};
+
+use rfd::MessageDialogResult;
+
+use crate::{is_gui_available, terminal::choices, DialogBackend, DialogRequest, Severity};
+
+/// ダイアログを表示するために呼び出すコマンドです。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DialogProgram {
+    /// GNOMEなどで使われる`zenity`です。
+    Zenity,
+    /// KDEで使われる`kdialog`です。
+    Kdialog,
+}
+
+impl DialogProgram {
+    fn command(self) -> &'static str {
+        match self {
+            Self::Zenity => "zenity",
+            Self::Kdialog => "kdialog",
+        }
+    }
+}
+
+/// `zenity`や`kdialog`のコマンドを呼び出してダイアログを表示するバックエンドです。
+/// `rfd`がダイアログを表示できない環境での代わりに使えます。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubprocessBackend {
+    program: DialogProgram,
+}
+
+impl SubprocessBackend {
+    pub fn new(program: DialogProgram) -> Self {
+        Self { program }
+    }
+
+    pub fn zenity() -> Self {
+        Self::new(DialogProgram::Zenity)
+    }
+
+    pub fn kdialog() -> Self {
+        Self::new(DialogProgram::Kdialog)
+    }
+
+    /// コマンドの引数と、終了コードと標準出力から押されたボタンを求める関数を作ります。
+    fn command(
+        &self,
+        request: &DialogRequest,
+    ) -> (Command, impl Fn(i32, &str) -> Option<MessageDialogResult>) {
+        let choices = choices(&request.buttons);
+        let labels: Vec<_> = choices.iter().map(|(label, _)| label.as_str()).collect();
+
+        let mut command = Command::new(self.program.command());
+        command.arg("--title").arg(&request.title);
+        match (self.program, &labels[..]) {
+            (DialogProgram::Zenity, [ok]) => {
+                let level = match request.severity {
+                    Severity::Error => "--error",
+                    Severity::Warning => "--warning",
+                    Severity::Info => "--info",
+                };
+                command.args([level, "--no-markup", "--ok-label", ok]);
+            }
+            (DialogProgram::Zenity, [ok, rest @ ..]) => {
+                command.args(["--question", "--no-markup", "--ok-label", ok]);
+                if let [extra, _] = rest {
+                    command.args(["--extra-button", extra]);
+                }
+                command.args(["--cancel-label", rest[rest.len() - 1]]);
+            }
+            (DialogProgram::Kdialog, [_]) => {
+                let level = match request.severity {
+                    Severity::Error => "--error",
+                    Severity::Warning => "--sorry",
+                    Severity::Info => "--msgbox",
+                };
+                command.arg(level);
+            }
+            (DialogProgram::Kdialog, [yes, no]) => {
+                command.args(["--yes-label", yes, "--no-label", no, "--yesno"]);
+            }
+            (DialogProgram::Kdialog, [yes, no, cancel, ..]) => {
+                command.args([
+                    "--yes-label",
+                    yes,
+                    "--no-label",
+                    no,
+                    "--cancel-label",
+                    cancel,
+                    "--yesnocancel",
+                ]);
+            }
+            (_, []) => {}
+        }
+        if self.program == DialogProgram::Zenity {
+            command.arg("--text");
+        }
+        command.arg(&request.description);
+
+        let program = self.program;
+        let parse = move |code: i32, stdout: &str| {
+            let index = match (program, code) {
+                (_, 0) => 0,
+                (DialogProgram::Zenity, 1) => {
+                    // 追加のボタンが押された場合は、そのラベルが出力されます。
+                    let extra = (choices.len() == 3)
+                        .then(|| choices.iter().position(|(label, _)| label == stdout.trim()))
+                        .flatten();
+                    extra.unwrap_or(choices.len() - 1)
+                }
+                (DialogProgram::Kdialog, 1 | 2) => (code as usize).min(choices.len() - 1),
+                _ => return None,
+            };
+            choices.get(index).map(|(_, result)| result.clone())
+        };
+
+        (command, parse)
+    }
+}
+
+/// `PATH`の中にコマンドがあるかどうかを調べます。
+fn find_in_path(command: &str) -> bool {
+    env::var_os("PATH")
+        .is_some_and(|paths| env::split_paths(&paths).any(|dir| dir.join(command).is_file()))
+}
+
+impl DialogBackend for SubprocessBackend {
+    fn show(&self, request: &DialogRequest) -> MessageDialogResult {
+        self.try_show(request).unwrap_or_default()
+    }
+
+    fn is_available(&self) -> bool {
+        is_gui_available() && find_in_path(self.program.command())
+    }
+
+    fn try_show(&self, request: &DialogRequest) -> io::Result<MessageDialogResult> {
+        let (mut command, parse) = self.command(request);
+
+        if !request.blocking {
+            let mut child = command
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()?;
+            // 終了したプロセスが残らないように、別のスレッドで待ちます。
+            thread::spawn(move || child.wait());
+            return Ok(MessageDialogResult::default());
+        }
+
+        let output = command
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        output
+            .status
+            .code()
+            .and_then(|code| parse(code, &stdout))
+            .ok_or_else(|| {
+                io::Error::other(format!(
+                    "{} exited with {}",
+                    self.program.command(),
+                    output.status
+                ))
+            })
+    }
+
+    fn name(&self) -> &str {
+        self.program.command()
+    }
+}
@@ -0,0 +1,179 @@
+use std::{
+    env,
+    io::{self, BufRead, IsTerminal, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        PoisonError, RwLock,
+    },
+};
+
+use rfd::{MessageButtons, MessageDialogResult};
+
+use crate::{get_message, DialogBackend, DialogRequest, MessageKey, Severity};
+
+/// ダイアログをどこに表示するかです。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DisplayMode {
+    /// GUIが使える場合はネイティブのダイアログ、使えない場合は標準エラー出力に表示します。
+    #[default]
+    Auto,
+    /// 常にネイティブのダイアログで表示します。
+    Gui,
+    /// 常に標準エラー出力に表示します。
+    Terminal,
+}
+
+static DISPLAY_MODE: RwLock<DisplayMode> = RwLock::new(DisplayMode::Auto);
+static TERMINAL_PROMPT: AtomicBool = AtomicBool::new(false);
+
+/// ダイアログをどこに表示するかを設定します。
+/// [`set_backend`](crate::set_backend)でバックエンドが設定されている場合は、そちらが優先されます。
+pub fn set_display_mode(mode: DisplayMode) {
+    *DISPLAY_MODE.write().unwrap_or_else(PoisonError::into_inner) = mode;
+}
+
+/// 現在の表示先を取得します。
+pub fn get_display_mode() -> DisplayMode {
+    *DISPLAY_MODE.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// `true`の場合、ターミナルに表示したダイアログのボタンを、標準入力から選べるようにします。
+/// 標準入力がTTYでない場合は尋ねません。デフォルトでは尋ねません。
+pub fn set_terminal_prompt(prompt: bool) {
+    TERMINAL_PROMPT.store(prompt, Ordering::SeqCst);
+}
+
+/// ターミナルでボタンを尋ねるかどうかを取得します。
+pub fn is_terminal_prompt_enabled() -> bool {
+    TERMINAL_PROMPT.load(Ordering::SeqCst)
+}
+
+/// ネイティブのダイアログを表示できる環境かどうかを取得します。
+/// LinuxなどのX11やWaylandを使う環境では、`DISPLAY`か`WAYLAND_DISPLAY`が設定されているかどうかで判断します。
+pub fn is_gui_available() -> bool {
+    if cfg!(all(
+        unix,
+        not(any(
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "android"
+        ))
+    )) {
+        ["DISPLAY", "WAYLAND_DISPLAY"]
+            .into_iter()
+            .any(|name| env::var_os(name).is_some_and(|value| !value.is_empty()))
+    } else {
+        true
+    }
+}
+
+/// ダイアログの内容を標準エラー出力に表示するバックエンドです。
+/// SSHやコンテナ、CIなど、GUIが使えない環境で使われます。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TerminalBackend;
+
+/// ボタンの名前と、押された時の結果の一覧です。最後のものがダイアログを閉じた時の扱いになります。
+pub(crate) fn choices(buttons: &MessageButtons) -> Vec<(String, MessageDialogResult)> {
+    let builtin = |key, result| (get_message(key), result);
+    let custom = |label: &String| (label.clone(), MessageDialogResult::Custom(label.clone()));
+
+    match buttons {
+        MessageButtons::Ok => vec![builtin(MessageKey::Ok, MessageDialogResult::Ok)],
+        MessageButtons::OkCancel => vec![
+            builtin(MessageKey::Ok, MessageDialogResult::Ok),
+            builtin(MessageKey::Cancel, MessageDialogResult::Cancel),
+        ],
+        MessageButtons::YesNo => vec![
+            builtin(MessageKey::Yes, MessageDialogResult::Yes),
+            builtin(MessageKey::No, MessageDialogResult::No),
+        ],
+        MessageButtons::YesNoCancel => vec![
+            builtin(MessageKey::Yes, MessageDialogResult::Yes),
+            builtin(MessageKey::No, MessageDialogResult::No),
+            builtin(MessageKey::Cancel, MessageDialogResult::Cancel),
+        ],
+        MessageButtons::OkCustom(a) => vec![custom(a)],
+        MessageButtons::OkCancelCustom(a, b) => vec![custom(a), custom(b)],
+        MessageButtons::YesNoCancelCustom(a, b, c) => vec![custom(a), custom(b), custom(c)],
+    }
+}
+
+/// `NO_COLOR`が設定されておらず、標準エラー出力がTTYの場合は色を付けます。
+fn paint(text: &str, severity: Severity) -> String {
+    if !io::stderr().is_terminal() || env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+        return text.to_owned();
+    }
+
+    let color = match severity {
+        Severity::Error => "31",
+        Severity::Warning => "33",
+        Severity::Info => "36",
+    };
+    format!("\x1b[1;{}m{}\x1b[0m", color, text)
+}
+
+/// 標準入力からボタンを選んでもらいます。番号か名前で選べ、入力が無くなった場合は`None`を返します。
+fn prompt(choices: &[(String, MessageDialogResult)]) -> Option<MessageDialogResult> {
+    let menu = choices
+        .iter()
+        .enumerate()
+        .map(|(i, (label, _))| format!("[{}] {}", i + 1, label))
+        .collect::<Vec<_>>()
+        .join("  ");
+
+    let mut stdin = io::stdin().lock();
+    loop {
+        eprint!("{} > ", menu);
+        let _ = io::stderr().flush();
+
+        let mut line = String::new();
+        if stdin.read_line(&mut line).ok()? == 0 {
+            return None;
+        }
+        let answer = line.trim();
+        let chosen = answer
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| choices.get(n.wrapping_sub(1)))
+            .or_else(|| {
+                choices
+                    .iter()
+                    .find(|(label, _)| label.eq_ignore_ascii_case(answer))
+            });
+        if let Some((_, result)) = chosen {
+            return Some(result.clone());
+        }
+    }
+}
+
+impl DialogBackend for TerminalBackend {
+    fn show(&self, request: &DialogRequest) -> MessageDialogResult {
+        self.try_show(request).unwrap_or_default()
+    }
+
+    fn try_show(&self, request: &DialogRequest) -> io::Result<MessageDialogResult> {
+        writeln!(
+            io::stderr(),
+            "\n{}\n{}\n",
+            paint(&request.title, request.severity),
+            request.text
+        )?;
+
+        let mut choices = choices(&request.buttons);
+        let ask = request.blocking
+            && choices.len() > 1
+            && is_terminal_prompt_enabled()
+            && io::stdin().is_terminal();
+        if ask {
+            if let Some(result) = prompt(&choices) {
+                return Ok(result);
+            }
+        }
+
+        Ok(choices.pop().map(|(_, result)| result).unwrap_or_default())
+    }
+
+    fn name(&self) -> &str {
+        "terminal"
+    }
+}
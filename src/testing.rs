@@ -0,0 +1,236 @@
+//! ダイアログを実際には表示せず、記録するだけのテスト用のバックエンドです。
+//! ディスプレイの無いCIでも、ユーザーが見るはずだった内容を検証できます。
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use rfd::MessageDialogResult;
+
+use crate::{set_backend, set_thread_backend, DialogBackend, DialogRequest};
+
+/// 表示を要求されたダイアログを全て記録するバックエンドです。
+/// クローンしたものは記録を共有します。
+#[derive(Debug, Clone, Default)]
+pub struct RecordingBackend {
+    records: Arc<Mutex<Vec<DialogRequest>>>,
+    responses: Arc<Mutex<VecDeque<MessageDialogResult>>>,
+}
+
+impl RecordingBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 新しい`RecordingBackend`をこのスレッドのバックエンドとして設定し、記録を見るためのハンドルを返します。
+    /// 他のスレッドのダイアログは記録されないため、並列に実行されるテストが互いの記録を壊すことはありません。
+    pub fn install() -> Self {
+        let backend = Self::new();
+        set_thread_backend(backend.clone());
+        backend
+    }
+
+    /// 新しい`RecordingBackend`をグローバルのバックエンドとして設定し、記録を見るためのハンドルを返します。
+    /// テストの中で別のスレッドからダイアログを表示する場合に使います。
+    /// 全てのテストで共有されるため、これを使うテストは直列に実行する必要があります。
+    pub fn install_global() -> Self {
+        let backend = Self::new();
+        set_backend(backend.clone());
+        backend
+    }
+
+    /// これまでに記録されたダイアログを全て取得します。
+    pub fn records(&self) -> Vec<DialogRequest> {
+        self.lock().clone()
+    }
+
+    /// これまでに記録されたダイアログを全て取り出し、記録を空にします。
+    pub fn take(&self) -> Vec<DialogRequest> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    /// 最後に記録されたダイアログを取得します。
+    pub fn last(&self) -> Option<DialogRequest> {
+        self.lock().last().cloned()
+    }
+
+    /// 次に表示されるダイアログで押されたことにするボタンを追加します。
+    /// 追加された順に使われ、無くなった後は`MessageDialogResult::Ok`が返されます。
+    pub fn push_response(&self, response: MessageDialogResult) {
+        self.responses
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push_back(response);
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// ダイアログが一つも表示されていないことを確認します。
+    #[track_caller]
+    pub fn assert_no_dialog(&self) {
+        let records = self.lock();
+        assert!(
+            records.is_empty(),
+            "expected no dialog, but {} were shown: {:#?}",
+            records.len(),
+            records
+        );
+    }
+
+    /// 表示されたダイアログの数を確認します。
+    #[track_caller]
+    pub fn assert_count(&self, count: usize) {
+        let records = self.lock();
+        assert_eq!(
+            records.len(),
+            count,
+            "unexpected number of dialogs: {:#?}",
+            records
+        );
+    }
+
+    /// 最後に表示されたダイアログが指定されたタイトルで、全文に`text`を含むことを確認します。
+    #[track_caller]
+    pub fn assert_last(&self, title: &str, text: &str) {
+        let last = self.last().expect("expected a dialog, but none were shown");
+        assert_eq!(last.title, title, "unexpected dialog title");
+        assert!(
+            last.text.contains(text),
+            "expected dialog text to contain {:?}, but it was {:?}",
+            text,
+            last.text
+        );
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<DialogRequest>> {
+        self.records.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl DialogBackend for RecordingBackend {
+    fn show(&self, request: &DialogRequest) -> MessageDialogResult {
+        self.lock().push(request.clone());
+        self.responses
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pop_front()
+            .unwrap_or(MessageDialogResult::Ok)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use super::*;
+    use crate::{get_title, ErrorDialogUnwrapper, Severity};
+
+    #[test]
+    fn records_ok_unwrap_or_dialog() {
+        let recorder = RecordingBackend::install();
+
+        let value = Err::<i32, _>(anyhow!("failed to load")).ok_unwrap_or_dialog();
+
+        assert_eq!(value, None);
+        recorder.assert_count(1);
+        recorder.assert_last(&get_title(), "failed to load");
+        let last = recorder.last().unwrap();
+        assert_eq!(last.severity, Severity::Error);
+        assert!(!last.blocking);
+    }
+
+    struct FakeWindow;
+
+    impl raw_window_handle::HasWindowHandle for FakeWindow {
+        fn window_handle(
+            &self,
+        ) -> Result<raw_window_handle::WindowHandle<'_>, raw_window_handle::HandleError> {
+            let raw = raw_window_handle::WebWindowHandle::new(1).into();
+            // SAFETY: Webのハンドルは数値を持つだけで、何も指していません。
+            Ok(unsafe { raw_window_handle::WindowHandle::borrow_raw(raw) })
+        }
+    }
+
+    impl raw_window_handle::HasDisplayHandle for FakeWindow {
+        fn display_handle(
+            &self,
+        ) -> Result<raw_window_handle::DisplayHandle<'_>, raw_window_handle::HandleError> {
+            Ok(raw_window_handle::DisplayHandle::web())
+        }
+    }
+
+    #[test]
+    fn scoped_parent_waits_for_the_dialog() {
+        let recorder = RecordingBackend::install();
+
+        let value =
+            Err::<i32, _>(anyhow!("failed to save")).ok_unwrap_or_dialog_with_parent(&FakeWindow);
+
+        assert_eq!(value, None);
+        let last = recorder.last().unwrap();
+        assert!(last.blocking);
+        assert!(last.parent.is_some());
+        assert!(crate::get_parent_window().is_none());
+    }
+
+    #[test]
+    fn ok_value_shows_no_dialog() {
+        let recorder = RecordingBackend::install();
+
+        let value = Ok::<_, anyhow::Error>(1).ok_unwrap_or_dialog();
+
+        assert_eq!(value, Some(1));
+        recorder.assert_no_dialog();
+    }
+
+    #[test]
+    fn accepts_borrowed_errors() {
+        let recorder = RecordingBackend::install();
+        let mutex = std::sync::Mutex::new(1);
+
+        let value = mutex.lock().ok_unwrap_or_dialog().map(|guard| *guard);
+
+        assert_eq!(value, Some(1));
+        recorder.assert_no_dialog();
+    }
+
+    mod probe {
+        fn describe(name: &str) -> String {
+            format!("failed to open {}", name)
+        }
+
+        crate::define_unwrapper!("Probe", describe(name: &str));
+    }
+
+    #[test]
+    fn defined_unwrapper_reports_the_caller() {
+        let recorder = RecordingBackend::install();
+
+        assert_eq!(probe::unwrap_or_dialog(Ok(1), "a.txt"), 1);
+        let line = line!() + 1;
+        let value = probe::ok_unwrap_or_dialog::<()>(Err(anyhow!("missing")), "a.txt");
+
+        assert_eq!(value, None);
+        recorder.assert_last("Probe", "failed to open a.txt");
+        let location = recorder.last().unwrap().location.unwrap();
+        assert_eq!((location.file(), location.line()), (file!(), line));
+    }
+
+    #[test]
+    fn install_is_local_to_the_thread() {
+        let recorder = RecordingBackend::install();
+
+        std::thread::spawn(|| {
+            let other = RecordingBackend::install();
+            let _ = Err::<(), _>(anyhow!("other thread")).ok_unwrap_or_dialog();
+            other.assert_count(1);
+        })
+        .join()
+        .unwrap();
+
+        recorder.assert_no_dialog();
+    }
+}
@@ -0,0 +1,305 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, VecDeque},
+    sync::{Mutex, PoisonError, RwLock},
+    time::{Duration, Instant},
+};
+
+use crate::{get_message, logging, DialogRequest, MessageKey};
+
+/// 同じエラーのダイアログが繰り返し表示されないようにする設定です。
+/// 致命的なエラーと、再試行のダイアログには適用されません。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Throttle {
+    /// 同じダイアログを再び表示しない期間です。`None`の場合は重複を抑制しません。
+    /// ダイアログはタイトルとエラーの全文か、[`with_dedup_key`]で指定されたキーで区別されます。
+    pub dedup: Option<Duration>,
+    /// `true`の場合、抑制した回数を次に表示するダイアログに書き加えます。
+    pub show_suppressed_count: bool,
+    /// 期間内に表示できるダイアログの最大の数です。`None`の場合は制限しません。
+    pub rate_limit: Option<(usize, Duration)>,
+}
+
+impl Throttle {
+    /// 何も抑制しない設定です。
+    pub const fn new() -> Self {
+        Self {
+            dedup: None,
+            show_suppressed_count: true,
+            rate_limit: None,
+        }
+    }
+
+    pub fn dedup(mut self, window: Duration) -> Self {
+        self.dedup = Some(window);
+        self
+    }
+
+    pub fn show_suppressed_count(mut self, show: bool) -> Self {
+        self.show_suppressed_count = show;
+        self
+    }
+
+    /// `per`の間に最大で`max`個までしかダイアログを表示しないようにします。
+    pub fn rate_limit(mut self, max: usize, per: Duration) -> Self {
+        self.rate_limit = Some((max, per));
+        self
+    }
+}
+
+impl Default for Throttle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static THROTTLE: RwLock<Throttle> = RwLock::new(Throttle::new());
+
+/// 重複の抑制と表示の数の制限を設定します。デフォルトでは何も抑制しません。
+pub fn set_throttle(throttle: Throttle) {
+    *THROTTLE.write().unwrap_or_else(PoisonError::into_inner) = throttle;
+}
+
+/// 現在の重複の抑制と表示の数の制限の設定を取得します。
+pub fn get_throttle() -> Throttle {
+    *THROTTLE.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+thread_local! {
+    /// [`with_dedup_key`]で設定された、このスレッドでのキーです。最後のものが使われます。
+    static SCOPED_KEYS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// 関数の中でこのスレッドから表示されるダイアログを、重複の抑制では全て同じものとして扱います。
+/// ポーリングのように、毎回少しずつ違う文章のエラーが出る処理に使います。
+pub fn with_dedup_key<R>(key: impl Into<String>, f: impl FnOnce() -> R) -> R {
+    struct Guard;
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            SCOPED_KEYS.with_borrow_mut(|keys| keys.pop());
+        }
+    }
+
+    SCOPED_KEYS.with_borrow_mut(|keys| keys.push(key.into()));
+    let _guard = Guard;
+    f()
+}
+
+struct Seen {
+    shown_at: Instant,
+    suppressed: usize,
+}
+
+struct State {
+    seen: BTreeMap<String, Seen>,
+    recent: VecDeque<Instant>,
+}
+
+impl State {
+    const fn new() -> Self {
+        Self {
+            seen: BTreeMap::new(),
+            recent: VecDeque::new(),
+        }
+    }
+
+    /// `now`の時点でダイアログを表示してよいかどうかを判断します。
+    fn admit(
+        &mut self,
+        throttle: &Throttle,
+        key: String,
+        request: &mut DialogRequest,
+        now: Instant,
+    ) -> bool {
+        let Self { seen, recent } = self;
+
+        let mut expired = None;
+        if let Some(window) = throttle.dedup {
+            if let Some(seen) = seen.get_mut(&key) {
+                if now - seen.shown_at < window {
+                    seen.suppressed += 1;
+                    return false;
+                }
+            }
+            expired = seen.remove(&key);
+
+            // 期間が過ぎたダイアログは忘れます。同じダイアログがもう表示されないかもしれないため、抑制した回数はログに残します。
+            seen.retain(|key, seen| {
+                let keep = now - seen.shown_at < window;
+                if !keep && seen.suppressed > 0 {
+                    logging::log_suppressed(key, seen.suppressed);
+                }
+                keep
+            });
+        }
+
+        if let Some((max, per)) = throttle.rate_limit {
+            while recent
+                .front()
+                .is_some_and(|&shown_at| now - shown_at >= per)
+            {
+                recent.pop_front();
+            }
+            if recent.len() >= max {
+                if let Some(mut expired) = expired {
+                    expired.suppressed += 1;
+                    seen.insert(key, expired);
+                }
+                return false;
+            }
+            recent.push_back(now);
+        }
+
+        if throttle.dedup.is_some() {
+            seen.insert(
+                key,
+                Seen {
+                    shown_at: now,
+                    suppressed: 0,
+                },
+            );
+            let suppressed = expired.map_or(0, |seen| seen.suppressed);
+            if throttle.show_suppressed_count && suppressed > 0 {
+                let note = format!(
+                    "\n\n{}",
+                    get_message(MessageKey::Suppressed).replace("{n}", &suppressed.to_string())
+                );
+                request.text.push_str(&note);
+                request.description.push_str(&note);
+            }
+        }
+
+        true
+    }
+}
+
+static STATE: Mutex<State> = Mutex::new(State::new());
+
+/// ダイアログを表示してよいかどうかを判断します。抑制する場合は`false`を返します。
+/// 表示する場合、設定によっては抑制した回数を`request`に書き加えます。
+pub(crate) fn admit(request: &mut DialogRequest) -> bool {
+    let throttle = get_throttle();
+    if throttle.dedup.is_none() && throttle.rate_limit.is_none() {
+        return true;
+    }
+
+    let key = SCOPED_KEYS
+        .with_borrow(|keys| keys.last().cloned())
+        .unwrap_or_else(|| format!("{}\n{}", request.title, request.text));
+    STATE.lock().unwrap_or_else(PoisonError::into_inner).admit(
+        &throttle,
+        key,
+        request,
+        Instant::now(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use rfd::MessageButtons;
+
+    use super::*;
+    use crate::Severity;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    fn request(text: &str) -> DialogRequest {
+        DialogRequest {
+            title: String::from("Error"),
+            text: text.to_owned(),
+            description: text.to_owned(),
+            severity: Severity::Error,
+            buttons: MessageButtons::Ok,
+            blocking: false,
+            location: None,
+            parent: None,
+        }
+    }
+
+    /// `start`から`secs`秒後に`text`のダイアログを表示しようとし、表示されたかと書き加えられた文章を返します。
+    fn admit(
+        state: &mut State,
+        throttle: &Throttle,
+        start: Instant,
+        secs: u64,
+        text: &str,
+    ) -> (bool, String) {
+        let mut request = request(text);
+        let key = format!("{}\n{}", request.title, request.text);
+        let admitted = state.admit(throttle, key, &mut request, start + SECOND * secs as u32);
+        (admitted, request.text)
+    }
+
+    fn suppressed(n: usize) -> String {
+        get_message(MessageKey::Suppressed).replace("{n}", &n.to_string())
+    }
+
+    #[test]
+    fn dedup_suppresses_repeats_within_the_window() {
+        let (mut state, start) = (State::new(), Instant::now());
+        let throttle = Throttle::new().dedup(SECOND * 60);
+
+        assert_eq!(
+            admit(&mut state, &throttle, start, 0, "a"),
+            (true, "a".into())
+        );
+        assert!(!admit(&mut state, &throttle, start, 10, "a").0);
+        assert!(admit(&mut state, &throttle, start, 20, "b").0);
+        assert!(!admit(&mut state, &throttle, start, 59, "a").0);
+
+        let (admitted, text) = admit(&mut state, &throttle, start, 60, "a");
+        assert!(admitted);
+        assert!(text.ends_with(&suppressed(2)));
+    }
+
+    #[test]
+    fn expired_entries_are_evicted() {
+        let (mut state, start) = (State::new(), Instant::now());
+        let throttle = Throttle::new().dedup(SECOND * 60);
+
+        for secs in 0..10 {
+            admit(
+                &mut state,
+                &throttle,
+                start,
+                secs,
+                &format!("error {}", secs),
+            );
+        }
+        assert!(!admit(&mut state, &throttle, start, 30, "error 0").0);
+        assert_eq!(state.seen.len(), 10);
+
+        admit(&mut state, &throttle, start, 120, "other");
+        assert_eq!(state.seen.len(), 1);
+        assert!(state.seen.contains_key("Error\nother"));
+    }
+
+    #[test]
+    fn rate_limit_allows_max_per_window() {
+        let (mut state, start) = (State::new(), Instant::now());
+        let throttle = Throttle::new().rate_limit(2, SECOND * 10);
+
+        assert!(admit(&mut state, &throttle, start, 0, "a").0);
+        assert!(admit(&mut state, &throttle, start, 1, "b").0);
+        assert!(!admit(&mut state, &throttle, start, 2, "c").0);
+        assert!(!admit(&mut state, &throttle, start, 9, "d").0);
+        assert!(admit(&mut state, &throttle, start, 10, "e").0);
+        assert!(!admit(&mut state, &throttle, start, 10, "f").0);
+        assert!(admit(&mut state, &throttle, start, 11, "g").0);
+    }
+
+    #[test]
+    fn rate_limited_repeats_are_counted() {
+        let (mut state, start) = (State::new(), Instant::now());
+        let throttle = Throttle::new().dedup(SECOND * 5).rate_limit(1, SECOND * 60);
+
+        assert!(admit(&mut state, &throttle, start, 0, "a").0);
+        assert!(!admit(&mut state, &throttle, start, 10, "a").0);
+        assert!(!admit(&mut state, &throttle, start, 20, "a").0);
+
+        let (admitted, text) = admit(&mut state, &throttle, start, 60, "a");
+        assert!(admitted);
+        assert!(text.ends_with(&suppressed(2)));
+    }
+}
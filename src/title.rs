@@ -0,0 +1,64 @@
+use std::{
+    cell::RefCell,
+    sync::{PoisonError, RwLock},
+};
+
+use crate::{get_message, MessageKey, Severity};
+
+static DEFAULT_TITLE: RwLock<Option<String>> = RwLock::new(None);
+
+thread_local! {
+    /// [`with_title`]で設定された、このスレッドでのタイトルです。最後のものが使われます。
+    static SCOPED_TITLES: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// 予期せぬエラーのダイアログのデフォルトのタイトルを設定します。
+/// 何度でも変更でき、言語の切り替えなどに使えます。
+pub fn set_title(title: impl Into<String>) {
+    *DEFAULT_TITLE
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(title.into());
+}
+
+/// `get_title`を使って予期せぬエラーのタイトルを取得します。
+/// [`with_title`]の中では、そこで指定されたタイトルが取得されます。
+/// もし設定されていない場合、現在のロケールでの"Unexpected Error"が取得されます。
+pub fn get_title() -> String {
+    scoped_title().unwrap_or_else(|| {
+        DEFAULT_TITLE
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+            .unwrap_or_else(|| get_message(MessageKey::UnexpectedError))
+    })
+}
+
+fn scoped_title() -> Option<String> {
+    SCOPED_TITLES.with_borrow(|titles| titles.last().cloned())
+}
+
+/// 重要度ごとのデフォルトのタイトルを取得します。
+/// エラーの場合と、[`with_title`]の中では[`get_title`]と同じです。
+pub fn get_severity_title(severity: Severity) -> String {
+    match severity {
+        Severity::Error => get_title(),
+        Severity::Warning => scoped_title().unwrap_or_else(|| get_message(MessageKey::Warning)),
+        Severity::Info => scoped_title().unwrap_or_else(|| get_message(MessageKey::Information)),
+    }
+}
+
+/// 関数の中でこのスレッドから表示される全てのダイアログのデフォルトのタイトルを、一時的に変更します。
+/// `unwrap_or_dialog_with_title`などでタイトルを明示した場合は、そちらが優先されます。
+pub fn with_title<R>(title: impl Into<String>, f: impl FnOnce() -> R) -> R {
+    struct Guard;
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            SCOPED_TITLES.with_borrow_mut(|titles| titles.pop());
+        }
+    }
+
+    SCOPED_TITLES.with_borrow_mut(|titles| titles.push(title.into()));
+    let _guard = Guard;
+    f()
+}
@@ -0,0 +1,210 @@
+use std::{
+    borrow::Cow,
+    sync::{PoisonError, RwLock},
+};
+
+use crate::{get_message, MessageKey};
+
+/// 何を単位にエラーの説明を省略するかです。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TruncateBy {
+    /// 指定された文字数を超えた分を省略します。
+    Chars(usize),
+    /// 指定された行数を超えた分を省略します。
+    Lines(usize),
+    /// 指定された書記素クラスタの数を超えた分を省略します。
+    /// 絵文字や結合文字が途中で切れないようになります。
+    /// `graphemes`フィーチャーが無効な場合は、[`TruncateBy::Chars`]と同じになります。
+    Graphemes(usize),
+}
+
+/// ダイアログに表示するエラーの説明の省略の仕方です。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Truncation {
+    pub by: TruncateBy,
+    /// 省略した時に末尾に付ける文字列です。`None`の場合は現在のロケールのものが使われます。
+    pub ellipsis: Option<Cow<'static, str>>,
+    /// `true`の場合、省略した時に"(N more characters)"のように省略した量を書き加えます。
+    pub footer: bool,
+}
+
+impl Truncation {
+    const DEFAULT: Self = Self {
+        by: TruncateBy::Chars(512),
+        ellipsis: None,
+        footer: false,
+    };
+
+    pub fn new(by: TruncateBy) -> Self {
+        Self {
+            by,
+            ..Self::DEFAULT
+        }
+    }
+
+    pub fn ellipsis(mut self, ellipsis: impl Into<Cow<'static, str>>) -> Self {
+        self.ellipsis = Some(ellipsis.into());
+        self
+    }
+
+    pub fn footer(mut self, footer: bool) -> Self {
+        self.footer = footer;
+        self
+    }
+
+    /// 省略する位置のバイト単位のインデックスと、省略される量を返します。
+    /// 省略する必要が無い場合は`None`を返します。
+    fn cut(&self, text: &str) -> Option<(usize, usize)> {
+        let by_chars = |max| {
+            text.char_indices()
+                .nth(max)
+                .map(|(index, _)| (index, text[index..].chars().count()))
+        };
+
+        match self.by {
+            TruncateBy::Chars(max) => by_chars(max),
+            TruncateBy::Lines(max) => {
+                let index = match max.checked_sub(1) {
+                    Some(nth) => text.match_indices('\n').nth(nth)?.0,
+                    None if text.is_empty() => return None,
+                    None => 0,
+                };
+                let rest = text[index..].strip_prefix('\n').unwrap_or(&text[index..]);
+                (!rest.is_empty()).then(|| (index, rest.lines().count()))
+            }
+            #[cfg(feature = "graphemes")]
+            TruncateBy::Graphemes(max) => {
+                use unicode_segmentation::UnicodeSegmentation;
+
+                text.grapheme_indices(true)
+                    .nth(max)
+                    .map(|(index, _)| (index, text[index..].graphemes(true).count()))
+            }
+            #[cfg(not(feature = "graphemes"))]
+            TruncateBy::Graphemes(max) => by_chars(max),
+        }
+    }
+
+    /// 文章を省略します。省略する必要が無い場合はそのまま返します。
+    pub fn apply<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let Some((index, rest)) = self.cut(text) else {
+            return Cow::Borrowed(text);
+        };
+
+        let ellipsis = match &self.ellipsis {
+            Some(ellipsis) => ellipsis.clone(),
+            None => Cow::Owned(get_message(MessageKey::Ellipsis)),
+        };
+
+        let mut truncated = format!("{}{}", &text[..index], ellipsis);
+        if self.footer {
+            let footer = match self.by {
+                TruncateBy::Lines(_) => MessageKey::MoreLines,
+                _ => MessageKey::MoreCharacters,
+            };
+            truncated.push('\n');
+            truncated.push_str(&get_message(footer).replace("{n}", &rest.to_string()));
+        }
+
+        Cow::Owned(truncated)
+    }
+}
+
+impl Default for Truncation {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+static TRUNCATION: RwLock<Truncation> = RwLock::new(Truncation::DEFAULT);
+
+/// エラーの説明の省略の仕方を設定します。
+/// デフォルトでは512文字を超えた分が`...`（日本語では`…`）に置き換えられます。
+pub fn set_truncation(truncation: Truncation) {
+    *TRUNCATION.write().unwrap_or_else(PoisonError::into_inner) = truncation;
+}
+
+/// 現在のエラーの説明の省略の仕方を取得します。
+pub fn get_truncation() -> Truncation {
+    TRUNCATION
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn truncation(by: TruncateBy) -> Truncation {
+        Truncation::new(by).ellipsis("~")
+    }
+
+    #[test]
+    fn short_text_is_not_cut() {
+        let text = "short";
+
+        assert!(matches!(
+            truncation(TruncateBy::Chars(5)).apply(text),
+            Cow::Borrowed("short")
+        ));
+        assert_eq!(truncation(TruncateBy::Lines(1)).apply(text), "short");
+        assert_eq!(
+            truncation(TruncateBy::Chars(5)).footer(true).apply(text),
+            "short"
+        );
+    }
+
+    #[test]
+    fn cut_by_chars() {
+        assert_eq!(
+            truncation(TruncateBy::Chars(3)).apply("あいうえお"),
+            "あいう~"
+        );
+    }
+
+    #[test]
+    fn cut_by_lines_with_trailing_newline() {
+        let text = "a\nb\nc\n";
+
+        assert_eq!(truncation(TruncateBy::Lines(2)).apply(text), "a\nb~");
+        assert_eq!(truncation(TruncateBy::Lines(3)).apply(text), text);
+    }
+
+    #[test]
+    fn zero_lines() {
+        assert_eq!(truncation(TruncateBy::Lines(0)).apply("a\nb"), "~");
+        assert_eq!(truncation(TruncateBy::Lines(0)).apply(""), "");
+    }
+
+    #[test]
+    fn cut_by_graphemes() {
+        assert_eq!(truncation(TruncateBy::Graphemes(2)).apply("abc"), "ab~");
+    }
+
+    #[cfg(feature = "graphemes")]
+    #[test]
+    fn graphemes_keep_clusters_whole() {
+        let text = "e\u{301}e\u{301}e\u{301}";
+
+        assert_eq!(
+            truncation(TruncateBy::Graphemes(2)).apply(text),
+            "e\u{301}e\u{301}~"
+        );
+    }
+
+    #[test]
+    fn footer_counts_the_rest() {
+        let lines = truncation(TruncateBy::Lines(1))
+            .footer(true)
+            .apply("a\nb\nc");
+        let chars = truncation(TruncateBy::Chars(2)).footer(true).apply("abcde");
+
+        let footer = |key, n: &str| get_message(key).replace("{n}", n);
+        assert_eq!(lines, format!("a~\n{}", footer(MessageKey::MoreLines, "2")));
+        assert_eq!(
+            chars,
+            format!("ab~\n{}", footer(MessageKey::MoreCharacters, "3"))
+        );
+    }
+}